@@ -7,12 +7,12 @@ use chrono::{Datelike, Local, NaiveDate, Weekday};
 use rand::prelude::Rng;
 use structopt::StructOpt;
 
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 
-use mdlog::parser;
-use mdlog::types::{Birthday, Person};
+use mdlog::format::{Locale, LogFormat};
+use mdlog::parser::{self, tag};
+use mdlog::types::{LeapDayPolicy, Occasion, OccasionKind, Person};
 
 #[derive(Debug, StructOpt)]
 struct Input {
@@ -27,6 +27,12 @@ struct Input {
     /// The number of weeks to generate  
     #[structopt(name = "n_weeks", default_value = "1")]
     n_weeks: u32,
+    /// The date format to use in headings (strftime syntax)
+    #[structopt(long = "date-format", default_value = "%d.%m.%Y")]
+    date_format: String,
+    /// The language of the weekday names in headings (en or de)
+    #[structopt(long = "locale", default_value = "en")]
+    locale: Locale,
     #[structopt(flatten)]
     bd_config: BD,
 }
@@ -48,10 +54,22 @@ struct BD {
     /// Makes it a little easier to stay in touch
     #[structopt(short = "c", long = "generate-calls")]
     gen_calls: bool,
+    /// The yaml file to include other recurring occasions (anniversaries, memorials, ...) from.
+    /// Refer to [mdlog::parser::load_occasions_file] for details on the format
+    #[structopt(
+        long = "occasions-file",
+        default_value = "occasions.yml",
+        help = "The file to source other occasions from."
+    )]
+    occasions_file: PathBuf,
+    /// Whether to include the occasions from the occasions file when generating templates.
+    #[structopt(short = "o", long = "generate-occasions")]
+    include_occasions: bool,
+    /// Where to put birthdays and occasions on the 29th of February in non-leap years (feb28 or mar1)
+    #[structopt(long = "leap-day", default_value = "feb28")]
+    leap_day: LeapDayPolicy,
 }
 
-/// The date formatting to use
-const DATE_FMT: &str = "%d.%m.%Y";
 const CALL_PROBABILITY: f64 = 0.1;
 
 /// always print to stderr because we do use stdout for the generated templates
@@ -68,6 +86,7 @@ fn main() {
     );
 
     let today = Local::today().naive_local();
+    let format = LogFormat::new(&input.date_format, input.locale);
 
     let year = input.year.unwrap_or_else(|| {
         eprintln!("No year provided, defaulting to {}", today.year());
@@ -80,15 +99,23 @@ fn main() {
     );
 
     // pull in the birthday file
-    let bds: HashMap<(u32, u32), Vec<Person>> =
-        if input.bd_config.include_birthdays || input.bd_config.gen_calls {
-            read_and_prep_birthday_file(&input.bd_config.bd_file)
-        } else {
-            HashMap::new()
-        };
+    let people: Vec<Person> = if input.bd_config.include_birthdays || input.bd_config.gen_calls {
+        read_birthday_file(&input.bd_config.bd_file)
+    } else {
+        vec![]
+    };
+
+    // birthdays are just another kind of occasion
+    let mut occasions: Vec<Occasion> = if input.bd_config.include_occasions {
+        read_occasions_file(&input.bd_config.occasions_file)
+    } else {
+        vec![]
+    };
+    if input.bd_config.include_birthdays {
+        occasions.extend(people.iter().map(Person::occasion));
+    }
 
     // init for the call stuff
-    let people: Vec<_> = bds.values().flat_map(|x| x).collect();
     let mut rng = rand::thread_rng();
 
     // correct for 1 week so this prints 1 week instead of 2 when given 1 as an input
@@ -112,39 +139,41 @@ fn main() {
     while day <= last_day {
         // generate a heading every time we begin a week
         if day.weekday() == Weekday::Mon {
-            let end_of_week =
-                NaiveDate::from_isoywd(day.year(), day.iso_week().week(), Weekday::Sun);
-            println!(
-                "# Week {}, {} - {}\n",
-                day.iso_week().week(),
-                day.format(DATE_FMT),
-                end_of_week.format(DATE_FMT)
-            );
+            println!("{}{}\n", tag::WEEK, format.week_heading(day));
         }
 
-        println!("## {:?}, {}", day.weekday(), day.format(DATE_FMT));
-        if input.bd_config.include_birthdays {
-            let month_day = &(day.month(), day.day());
-            if let Some(people) = bds.get(month_day) {
-                people.iter().for_each(|p| {
-                    let age: String = match p.birthday {
-                        Birthday::KnownYear(bd) => Some(today.year() - bd.year()),
-                        _ => None,
+        println!("{}{}", tag::DAY, format.day_heading(day));
+        occasions
+            .iter()
+            .filter(|o| o.date.falls_on(day, input.bd_config.leap_day))
+            .for_each(|o| {
+                let policy = input.bd_config.leap_day;
+                // milestones are highlighted in bold
+                let years = o.years_on(day, policy).map(|y| {
+                    let y = match o.kind {
+                        OccasionKind::Birthday => format!("(Age {})", y),
+                        _ => format!("({} years)", y),
+                    };
+                    if o.is_milestone_on(day, policy) {
+                        format!(" **{}**", y)
+                    } else {
+                        format!(" {}", y)
                     }
-                    .map(|a| format!("(Age {})", a))
-                    .unwrap_or_else(|| "".into());
-                    // OPT: not happy with the string allocation here
-                    println!("- TODO: Congratulate {} {}", p.name, age);
                 });
-            }
-        }
+                let years = years.as_deref().unwrap_or("");
+                match o.kind {
+                    OccasionKind::Birthday => {
+                        println!("- TODO: Congratulate {}{}", o.title(), years)
+                    }
+                    kind => println!("- TODO: {}: {}{}", kind, o.title(), years),
+                }
+            });
         if input.bd_config.gen_calls && !people.is_empty() && rng.gen_bool(CALL_PROBABILITY) {
             let person_idx = rng.gen_range(0usize, people.len());
-            let person = people[person_idx];
+            let person = &people[person_idx];
             println!("- TODO: Call {}", person.name);
         }
         // insert an empty line (uses platform specific line end)
-        #[allow(clippy::println_empty_string)]
         println!();
 
         // next day
@@ -153,19 +182,22 @@ fn main() {
     eprintln!("Done");
 }
 
-fn read_and_prep_birthday_file(file: &PathBuf) -> HashMap<(u32, u32), Vec<Person>> {
-    let people = match parser::load_birthday_file(file) {
+fn read_birthday_file(file: &Path) -> Vec<Person> {
+    match parser::load_birthday_file(file) {
         Ok(p) => p,
         Err(e) => {
             eprintln!("Failed to parse birthday file with {}", e);
             process::exit(e.raw_os_error().unwrap_or(-1));
         }
-    };
+    }
+}
 
-    let mut m = HashMap::new();
-    people
-        .into_iter()
-        .map(|p| ((p.birthday.month(), p.birthday.day()), p))
-        .for_each(|p| m.entry(p.0).or_insert_with(|| vec![]).push(p.1));
-    m
+fn read_occasions_file(file: &Path) -> Vec<Occasion> {
+    match parser::load_occasions_file(file) {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("Failed to parse occasions file with {}", e);
+            process::exit(e.raw_os_error().unwrap_or(-1));
+        }
+    }
 }
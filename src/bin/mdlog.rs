@@ -0,0 +1,762 @@
+extern crate chrono;
+extern crate mdlog;
+extern crate serde_json;
+extern crate structopt;
+
+use chrono::naive::NaiveDate;
+use chrono::Local;
+use structopt::StructOpt;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+
+use mdlog::cache::ParseCache;
+use mdlog::csv::{self, EventColumn, TaskColumn};
+use mdlog::export::{self, Export, ExportFormat};
+use mdlog::format::{Locale, LogFormat};
+use mdlog::formatter;
+use mdlog::history;
+use mdlog::html;
+use mdlog::lint;
+use mdlog::logset::{self, LogSet, ParsedLog};
+use mdlog::org;
+use mdlog::parser::{self, MDLogParser, Tags, TaskStyle};
+use mdlog::search::{Index, Query};
+use mdlog::stats::{self, Period};
+use mdlog::stream;
+use mdlog::timesheet;
+use mdlog::todotxt;
+use mdlog::types::{self, LeapDayPolicy, OccasionKind, Person};
+
+/// The path standing for stdin
+const STDIN: &str = "-";
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "mdlog", about = "Work with MDLog files")]
+enum Command {
+    /// Bring MDLog files into canonical form and print the result to stdout
+    #[structopt(name = "fmt")]
+    Fmt {
+        /// Convert all tasks to the given style (keyword or checkbox)
+        #[structopt(long = "style")]
+        style: Option<TaskStyle>,
+        /// Write the result back to the files instead of printing it
+        #[structopt(short = "w", long = "write")]
+        write: bool,
+        /// Only list the files which are not formatted; exits non-zero if there are any
+        #[structopt(long = "check", conflicts_with = "write")]
+        check: bool,
+        /// The MDLog files, directories or glob patterns to format; - reads from stdin
+        #[structopt(name = "paths", required = true)]
+        paths: Vec<String>,
+    },
+    /// Check MDLog files for inconsistencies; exits non-zero if any are found
+    #[structopt(name = "lint")]
+    Lint {
+        /// How to print the problems (human or json)
+        #[structopt(long = "format", default_value = "human")]
+        format: OutputFormat,
+        #[structopt(flatten)]
+        log: LogOptions,
+    },
+    /// Print all tasks, events and entries of MDLog files in a machine readable format
+    #[structopt(name = "export")]
+    Export {
+        /// The format to export to (json, yaml or ndjson)
+        #[structopt(long = "format", default_value = "json")]
+        format: ExportFormat,
+        /// A birthday file whose people are exported as well; - reads it from stdin
+        #[structopt(long = "birthday-file")]
+        bd_file: Option<PathBuf>,
+        #[structopt(flatten)]
+        log: LogOptions,
+    },
+    /// Print the events or tasks of MDLog files as CSV
+    #[structopt(name = "csv")]
+    Csv {
+        /// What to print (events or tasks)
+        #[structopt(name = "items")]
+        items: CsvItems,
+        /// The character separating the fields
+        #[structopt(long = "delimiter", default_value = ",")]
+        delimiter: char,
+        /// The columns to print separated by commas, e.g. date,start,duration  
+        /// defaults to all columns
+        #[structopt(long = "columns")]
+        columns: Option<String>,
+        #[structopt(flatten)]
+        log: LogOptions,
+    },
+    /// Print the tasks of MDLog files in todo.txt format
+    #[structopt(name = "todotxt-export")]
+    TodoTxtExport {
+        #[structopt(flatten)]
+        log: LogOptions,
+    },
+    /// Add the tasks of a todo.txt file to a MDLog file and print the result to stdout  
+    /// Each task is put under the day it was created; tasks without dates under today
+    #[structopt(name = "todotxt-import")]
+    TodoTxtImport {
+        /// The todo.txt file to import
+        #[structopt(long = "from")]
+        todo_file: PathBuf,
+        /// Write the result back to the MDLog file instead of printing it
+        #[structopt(short = "w", long = "write")]
+        write: bool,
+        #[structopt(flatten)]
+        log: LogOptions,
+    },
+    /// Print MDLog files as Org files
+    #[structopt(name = "org-export")]
+    OrgExport {
+        #[structopt(flatten)]
+        log: LogOptions,
+    },
+    /// Print Org files written by org-export as MDLog file; the paths are Org files
+    #[structopt(name = "org-import")]
+    OrgImport {
+        #[structopt(flatten)]
+        log: LogOptions,
+    },
+    /// Print statistics of the tasks and events of MDLog files per week or month
+    #[structopt(name = "stats")]
+    Stats {
+        /// The period to group by (day, week or month)
+        #[structopt(long = "period", default_value = "week")]
+        period: Period,
+        /// How to print the statistics (human or json)
+        #[structopt(long = "format", default_value = "human")]
+        format: OutputFormat,
+        #[structopt(flatten)]
+        log: LogOptions,
+    },
+    /// Print the time spent on events per tag and day or week  
+    /// Events end at their end time or else at the start of the next event on the same day
+    #[structopt(name = "timesheet")]
+    Timesheet {
+        /// The period to group by (day, week or month)
+        #[structopt(long = "period", default_value = "week")]
+        period: Period,
+        /// How to print the timesheet (human or json)
+        #[structopt(long = "format", default_value = "human")]
+        format: OutputFormat,
+        #[structopt(flatten)]
+        log: LogOptions,
+    },
+    /// Print the history of every task copied from day to day: its first appearance,
+    /// each carry-over and its completion
+    #[structopt(name = "history")]
+    History {
+        /// Only print tasks whose message contains this text
+        #[structopt(long = "filter")]
+        filter: Option<String>,
+        /// How to print the histories (human or json)
+        #[structopt(long = "format", default_value = "human")]
+        format: OutputFormat,
+        #[structopt(flatten)]
+        log: LogOptions,
+    },
+    /// Search the tasks, events and entries of MDLog files  
+    /// Every word has to be found, "quoted phrases" in this order and #tags as tags
+    #[structopt(name = "search")]
+    Search {
+        /// What to search for, e.g. 'review "release notes" #work'
+        #[structopt(name = "query")]
+        query: Query,
+        /// The first day to search (YYYY-MM-DD)
+        #[structopt(long = "from")]
+        from: Option<NaiveDate>,
+        /// The last day to search (YYYY-MM-DD)
+        #[structopt(long = "to")]
+        to: Option<NaiveDate>,
+        /// Only items with this tag (repeatable)
+        #[structopt(long = "tag", number_of_values = 1)]
+        tags: Vec<String>,
+        /// The number of lines to print before and after each item
+        #[structopt(short = "C", long = "context", default_value = "0")]
+        context: usize,
+        /// The number of results to print at most
+        #[structopt(long = "limit")]
+        limit: Option<usize>,
+        #[structopt(flatten)]
+        log: LogOptions,
+    },
+    /// Print the birthdays and other occasions coming up within the next days
+    #[structopt(name = "upcoming")]
+    Upcoming {
+        /// The birthday file to read the birthdays from
+        #[structopt(long = "birthday-file", default_value = "birthdays.yml")]
+        bd_file: PathBuf,
+        /// An occasions file whose occasions are included as well
+        #[structopt(long = "occasions-file")]
+        occasions_file: Option<PathBuf>,
+        /// The number of days to look ahead
+        #[structopt(long = "days", default_value = "30")]
+        days: u32,
+        /// Where to put birthdays and occasions on the 29th of February in non-leap years (feb28 or mar1)
+        #[structopt(long = "leap-day", default_value = "feb28")]
+        leap_day: LeapDayPolicy,
+    },
+    /// Render MDLog files as static HTML pages with a calendar, a task board and a page per week
+    #[structopt(name = "html")]
+    Html {
+        /// The directory to write the pages to
+        #[structopt(short = "o", long = "out", default_value = "html")]
+        out: PathBuf,
+        #[structopt(flatten)]
+        log: LogOptions,
+    },
+}
+
+/// The heading format and files of the logs to work on
+#[derive(Debug, StructOpt)]
+struct LogOptions {
+    /// The date format used in headings (strftime syntax)
+    #[structopt(long = "date-format", default_value = "%d.%m.%Y")]
+    date_format: String,
+    /// The language of the weekday names in headings (en or de)
+    #[structopt(long = "locale", default_value = "en")]
+    locale: Locale,
+    /// Also read * items and DOING, WAIT and CANCELLED tasks
+    #[structopt(long = "extended-tags")]
+    extended_tags: bool,
+    /// The directory to cache parsed files in [default: ~/.cache/mdlog]
+    #[structopt(long = "cache-dir", parse(from_os_str))]
+    cache_dir: Option<PathBuf>,
+    /// Parse all files again instead of using the cache
+    #[structopt(long = "no-cache")]
+    no_cache: bool,
+    /// The MDLog files, directories or glob patterns to work on; - reads from stdin
+    #[structopt(name = "paths", required = true)]
+    paths: Vec<String>,
+}
+
+impl LogOptions {
+    fn parser(&self) -> MDLogParser {
+        let tags = if self.extended_tags {
+            Tags::extended()
+        } else {
+            Tags::default()
+        };
+        MDLogParser::builder()
+            .normalize(true)
+            .format(LogFormat::new(&self.date_format, self.locale))
+            .tags(tags)
+            .build()
+    }
+
+    /// Load all files with the given parser, through the cache unless it is turned off
+    fn load(&self, parser: &MDLogParser) -> io::Result<LogSet> {
+        let paths: Vec<_> = self.paths.iter().filter(|p| *p != STDIN).cloned().collect();
+        let files = if paths.is_empty() {
+            vec![]
+        } else {
+            find_files(&paths)?
+        };
+        let cache_dir = self.cache_dir.clone().or_else(ParseCache::default_dir);
+        let mut set = match cache_dir {
+            Some(dir) if !self.no_cache => {
+                LogSet::load_files_cached(&files, parser, &ParseCache::new(dir))?
+            }
+            _ => LogSet::load_files(&files, parser)?,
+        };
+
+        if self.reads_stdin() {
+            let stdin = io::stdin();
+            set.add_parsed(Path::new(STDIN), ParsedLog::read(stdin.lock(), parser)?);
+        }
+        Ok(set)
+    }
+
+    fn reads_stdin(&self) -> bool {
+        self.paths.iter().any(|p| p == STDIN)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum OutputFormat {
+    Human,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(OutputFormat::Human),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!(
+                "Unknown output format {} (expected human or json)",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CsvItems {
+    Events,
+    Tasks,
+}
+
+impl FromStr for CsvItems {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "events" => Ok(CsvItems::Events),
+            "tasks" => Ok(CsvItems::Tasks),
+            _ => Err(format!("Unknown items {} (expected events or tasks)", s)),
+        }
+    }
+}
+
+/// always print to stderr because we do use stdout for the output
+fn main() {
+    let result = match Command::from_args() {
+        Command::Fmt {
+            style,
+            write,
+            check,
+            paths,
+        } => find_files(&paths).and_then(|files| fmt(style, write, check, &files)),
+        Command::Lint { format, log } => {
+            find_files(&log.paths).and_then(|files| run_lint(&log.parser(), format, &files))
+        }
+        Command::Export {
+            format,
+            bd_file,
+            log,
+        } => export(&log, format, bd_file.as_deref()),
+        Command::Csv {
+            items,
+            delimiter,
+            columns,
+            log,
+        } => write_csv(&log, items, delimiter, columns.as_deref()),
+        Command::TodoTxtExport { log } => todotxt_export(&log),
+        Command::TodoTxtImport {
+            todo_file,
+            write,
+            log,
+        } => todotxt_import(&log, &todo_file, write),
+        Command::OrgExport { log } => org_export(&log),
+        Command::Html { out, log } => write_html(&log, &out),
+        Command::Search {
+            mut query,
+            from,
+            to,
+            tags,
+            context,
+            limit,
+            log,
+        } => {
+            query.from = from;
+            query.to = to;
+            query.tags.extend(
+                tags.iter()
+                    .map(|t| t.trim_start_matches('#').to_lowercase()),
+            );
+            search(&log, &query, context, limit)
+        }
+        Command::History {
+            filter,
+            format,
+            log,
+        } => print_history(&log, filter.as_deref(), format),
+        Command::Timesheet {
+            period,
+            format,
+            log,
+        } => print_timesheet(&log, period, format),
+        Command::Stats {
+            period,
+            format,
+            log,
+        } => print_stats(&log, period, format),
+        Command::Upcoming {
+            bd_file,
+            occasions_file,
+            days,
+            leap_day,
+        } => print_upcoming(&bd_file, occasions_file.as_deref(), days, leap_day),
+        Command::OrgImport { log } => org_import(&log),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Failed with {}", e);
+        process::exit(e.raw_os_error().unwrap_or(-1));
+    }
+}
+
+/// Resolve all paths given on the command line to MDLog files, keeping - for stdin
+fn find_files(paths: &[String]) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for path in paths {
+        if path == STDIN {
+            files.push(PathBuf::from(STDIN));
+        } else {
+            files.extend(logset::find_files(path)?);
+        }
+    }
+    Ok(files)
+}
+
+/// Read a file found by [find_files], or stdin for -
+fn read_file(file: &Path) -> io::Result<String> {
+    if file != Path::new(STDIN) {
+        return fs::read_to_string(file);
+    }
+    let mut log_data = String::new();
+    io::stdin().read_to_string(&mut log_data)?;
+    Ok(log_data)
+}
+
+fn fmt(style: Option<TaskStyle>, write: bool, check: bool, files: &[PathBuf]) -> io::Result<()> {
+    if write && files.iter().any(|f| f == Path::new(STDIN)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "stdin cannot be written back",
+        ));
+    }
+
+    let tags = Tags::default();
+    let mut unformatted = false;
+    for file in files {
+        let log_data = read_file(file)?;
+
+        let formatted = match style {
+            Some(style) => formatter::convert_task_style(&log_data, &tags, style),
+            None => log_data.clone(),
+        };
+        let formatted = formatter::canonicalize(&formatted, &tags);
+
+        if check {
+            if formatted != log_data {
+                println!("{}", file.display());
+                unformatted = true;
+            }
+        } else if write {
+            fs::write(file, formatted)?;
+        } else {
+            print!("{}", formatted);
+        }
+    }
+
+    if unformatted {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+/// Print all problems found in the given files; exits with 1 if there are any
+fn run_lint(parser: &MDLogParser, format: OutputFormat, files: &[PathBuf]) -> io::Result<()> {
+    let mut lints = vec![];
+    for file in files {
+        let log_data = read_file(file)?;
+        for mut l in lint::lint(parser, &log_data) {
+            l.span.file = Some(file.to_owned());
+            lints.push(l);
+        }
+    }
+
+    match format {
+        OutputFormat::Human => {
+            for l in &lints {
+                println!("{}", l);
+            }
+        }
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&lints).map_err(io::Error::from)?;
+            println!("{}", json);
+        }
+    }
+
+    if !lints.is_empty() {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn export(log: &LogOptions, format: ExportFormat, bd_file: Option<&Path>) -> io::Result<()> {
+    if bd_file == Some(Path::new(STDIN)) && log.reads_stdin() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "stdin cannot be read as both birthday file and log",
+        ));
+    }
+    let people = match bd_file {
+        Some(f) if f == Path::new(STDIN) => parser::read_people(io::stdin().lock())?,
+        Some(f) => parser::load_birthday_file(f)?,
+        None => vec![],
+    };
+    let stdout = io::stdout();
+
+    // items read from stdin alone are written as they come, however long the log is
+    if format == ExportFormat::Ndjson && log.paths == [STDIN] {
+        let parser = log.parser();
+        let stdin = io::stdin();
+        let items = stream::items(&parser, stdin.lock());
+        return export::write_ndjson_items(&mut stdout.lock(), items, &people);
+    }
+
+    let set = log.load(&log.parser())?;
+    Export::new(&set, people).write(&mut stdout.lock(), format)
+}
+
+fn write_csv(
+    log: &LogOptions,
+    items: CsvItems,
+    delimiter: char,
+    columns: Option<&str>,
+) -> io::Result<()> {
+    fn parse_columns<C: FromStr<Err = String>>(columns: &str) -> io::Result<Vec<C>> {
+        columns
+            .split(',')
+            .map(|c| C::from_str(c.trim()))
+            .collect::<Result<_, _>>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    }
+
+    let set = log.load(&log.parser())?;
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    match items {
+        CsvItems::Events => {
+            let columns = match columns {
+                Some(c) => parse_columns::<EventColumn>(c)?,
+                None => csv::EVENT_COLUMNS.to_vec(),
+            };
+            csv::write_events(&mut out, set.events(), &columns, delimiter)
+        }
+        CsvItems::Tasks => {
+            let columns = match columns {
+                Some(c) => parse_columns::<TaskColumn>(c)?,
+                None => csv::TASK_COLUMNS.to_vec(),
+            };
+            csv::write_tasks(
+                &mut out,
+                &history::collapse(set.tasks()),
+                &columns,
+                delimiter,
+            )
+        }
+    }
+}
+
+fn todotxt_export(log: &LogOptions) -> io::Result<()> {
+    let set = log.load(&log.parser())?;
+    for t in todotxt::from_tasks(&history::collapse(set.tasks())) {
+        println!("{}", t);
+    }
+    Ok(())
+}
+
+fn todotxt_import(log: &LogOptions, todo_file: &Path, write: bool) -> io::Result<()> {
+    let file = match log.paths.as_slice() {
+        [file] => Path::new(file),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Tasks can only be imported into a single file",
+            ))
+        }
+    };
+    let todos = todotxt::parse(&fs::read_to_string(todo_file)?)?;
+    // a file to import into may not exist yet
+    let log_data = if file.exists() {
+        fs::read_to_string(file)?
+    } else {
+        String::new()
+    };
+
+    let parser = log.parser();
+    let today = Local::today().naive_local();
+    let imported = todotxt::import(&log_data, &todos, parser.format(), parser.tags(), today);
+
+    if write {
+        fs::write(file, imported)
+    } else {
+        print!("{}", imported);
+        Ok(())
+    }
+}
+
+fn org_export(log: &LogOptions) -> io::Result<()> {
+    let parser = log.parser();
+    for file in find_files(&log.paths)? {
+        print!("{}", org::to_org(&parser, &read_file(&file)?));
+    }
+    Ok(())
+}
+
+/// Print the given Org files as log, with headings in the format of the options
+fn org_import(log: &LogOptions) -> io::Result<()> {
+    let parser = log.parser();
+    for path in &log.paths {
+        print!("{}", org::from_org(&parser, &read_file(Path::new(path))?));
+    }
+    Ok(())
+}
+
+fn write_html(log: &LogOptions, out: &Path) -> io::Result<()> {
+    let parser = log.parser();
+    let set = log.load(&parser)?;
+
+    fs::create_dir_all(out)?;
+    for page in html::render(&set, parser.format()) {
+        fs::write(out.join(&page.name), page.html)?;
+    }
+    Ok(())
+}
+
+fn print_stats(log: &LogOptions, period: Period, format: OutputFormat) -> io::Result<()> {
+    let parser = log.parser();
+    let set = log.load(&parser)?;
+    let today = Local::today().naive_local();
+    let tasks = history::collapse(set.tasks());
+    let stats = stats::stats(&tasks, set.events(), period, today);
+
+    match format {
+        OutputFormat::Human => {
+            for s in &stats {
+                println!("{}\n", s.summary(parser.format()));
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+        }
+    }
+    Ok(())
+}
+
+fn print_history(log: &LogOptions, filter: Option<&str>, format: OutputFormat) -> io::Result<()> {
+    let parser = log.parser();
+    let set = log.load(&parser)?;
+    let histories: Vec<_> = history::histories(set.tasks())
+        .into_iter()
+        .filter(|h| {
+            filter
+                .map(|f| h.tasks.iter().any(|t| t.msg.contains(f)))
+                .unwrap_or(true)
+        })
+        .collect();
+
+    match format {
+        OutputFormat::Human => {
+            for h in &histories {
+                println!("{}\n", h.summary(parser.format()));
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&histories)?);
+        }
+    }
+    Ok(())
+}
+
+/// Print each hit with its date and kind, followed by its lines and the given lines around it
+fn search(log: &LogOptions, query: &Query, context: usize, limit: Option<usize>) -> io::Result<()> {
+    let parser = log.parser();
+    let set = log.load(&parser)?;
+    let index = Index::new(&set);
+    let hits = index.search(query);
+
+    let mut files: HashMap<PathBuf, Vec<String>> = HashMap::new();
+    for hit in hits.iter().take(limit.unwrap_or(hits.len())) {
+        let d = hit.document;
+        let file = match &d.span.file {
+            Some(file) => file,
+            None => continue,
+        };
+        println!(
+            "{}:{}: {} ({})",
+            file.display(),
+            d.span.line,
+            parser.format().day_heading(d.date),
+            d.kind.name()
+        );
+
+        // stdin cannot be read again, so there is only the text of the item
+        if file == Path::new(STDIN) {
+            for (i, line) in d.text.lines().enumerate() {
+                println!("{:>5} | {}", d.span.line + i, line);
+            }
+            println!();
+            continue;
+        }
+        if !files.contains_key(file) {
+            let lines = fs::read_to_string(file)?
+                .lines()
+                .map(String::from)
+                .collect();
+            files.insert(file.clone(), lines);
+        }
+        let lines = &files[file];
+        // the lines of the item itself
+        let item_lines = d.text.lines().count().max(1);
+        let first = d.span.line.saturating_sub(1 + context);
+        let last = (d.span.line - 1 + item_lines + context).min(lines.len());
+        for (i, line) in lines.iter().enumerate().take(last).skip(first) {
+            println!("{:>5} | {}", i + 1, line);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+fn print_timesheet(log: &LogOptions, period: Period, format: OutputFormat) -> io::Result<()> {
+    let set = log.load(&log.parser())?;
+    let sheet = timesheet::timesheet(set.events(), period);
+
+    match format {
+        OutputFormat::Human => {
+            for p in &sheet {
+                println!("{}\n", p.summary());
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&sheet)?);
+        }
+    }
+    Ok(())
+}
+
+fn print_upcoming(
+    bd_file: &Path,
+    occasions_file: Option<&Path>,
+    days: u32,
+    policy: LeapDayPolicy,
+) -> io::Result<()> {
+    let mut occasions: Vec<_> = parser::load_birthday_file(bd_file)?
+        .iter()
+        .map(Person::occasion)
+        .collect();
+    if let Some(f) = occasions_file {
+        occasions.extend(parser::load_occasions_file(f)?);
+    }
+
+    let today = Local::today().naive_local();
+    let format = LogFormat::default();
+    for (date, o) in types::upcoming(&occasions, today, days, policy) {
+        let years = match (o.kind, o.years_on(date, policy)) {
+            (OccasionKind::Birthday, Some(y)) => format!(" (Age {})", y),
+            (_, Some(y)) => format!(" ({} years)", y),
+            (_, None) => String::new(),
+        };
+        println!(
+            "{}: {}: {}{}",
+            format.day_heading(date),
+            o.kind,
+            o.title(),
+            years
+        );
+    }
+    Ok(())
+}
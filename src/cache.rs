@@ -0,0 +1,161 @@
+use crate::logset::ParsedLog;
+use crate::parser::MDLogParser;
+use serde::{Deserialize, Serialize};
+
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Entries written by other versions are not used, the parse results may differ
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The parse result of a file along with what it depends on
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    version: String,
+    path: PathBuf,
+    modified: SystemTime,
+    content_hash: u64,
+    parser_hash: u64,
+    parsed: ParsedLog,
+}
+
+/// A directory of parse results, one file per parsed log.
+/// An entry is used as long as the version of mdlog, the path, the modification time
+/// and the content of the file and the configuration of the parser are unchanged.
+/// Otherwise the file is parsed again and the entry replaced.
+#[derive(Clone, Debug)]
+pub struct ParseCache {
+    dir: PathBuf,
+}
+
+impl ParseCache {
+    /// A cache in the given directory; it is created on the first write
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        ParseCache { dir: dir.into() }
+    }
+
+    /// ```$XDG_CACHE_HOME/mdlog``` or ```~/.cache/mdlog```, if either is set
+    pub fn default_dir() -> Option<PathBuf> {
+        env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .map(|dir| dir.join("mdlog"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The parse result of the given file, from the cache if the entry is still valid
+    pub fn parse(&self, file: &Path, parser: &MDLogParser) -> io::Result<ParsedLog> {
+        let path = fs::canonicalize(file)?;
+        let modified = fs::metadata(&path)?.modified()?;
+        let log_data = fs::read_to_string(&path)?;
+        let content_hash = hash(&log_data);
+        let parser_hash = hash(&format!("{:?}", parser));
+
+        let entry_file = self.entry_file(&path);
+        if let Some(entry) = read_entry(&entry_file) {
+            if entry.version == VERSION
+                && entry.path == path
+                && entry.modified == modified
+                && entry.content_hash == content_hash
+                && entry.parser_hash == parser_hash
+            {
+                return Ok(entry.parsed);
+            }
+        }
+
+        let entry = CacheEntry {
+            version: VERSION.to_string(),
+            path,
+            modified,
+            content_hash,
+            parser_hash,
+            parsed: ParsedLog::parse(&log_data, parser)?,
+        };
+        // the cache only saves time, so failing to write it is no reason to fail
+        let _ = self.write_entry(&entry_file, &entry);
+        Ok(entry.parsed)
+    }
+
+    /// Remove all entries
+    pub fn clear(&self) -> io::Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(())
+    }
+
+    fn entry_file(&self, path: &Path) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", hash(&path)))
+    }
+
+    fn write_entry(&self, entry_file: &Path, entry: &CacheEntry) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_string(entry).map_err(io::Error::from)?;
+        // write to a temporary file first so concurrent runs never read half an entry
+        let tmp = entry_file.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, entry_file)
+    }
+}
+
+/// A missing or unreadable entry is the same as no entry
+fn read_entry(entry_file: &Path) -> Option<CacheEntry> {
+    let json = fs::read_to_string(entry_file).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Stable for a given build of mdlog, which is all the cache needs
+fn hash<T: Hash + ?Sized>(t: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    t.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn invalidation() {
+        let dir = env::temp_dir().join(format!("mdlog-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("log.md");
+        fs::write(&file, "## Mon, 14.10.2019\n- TODO: a\n").unwrap();
+
+        let cache = ParseCache::new(dir.join("cache"));
+        let p = MDLogParser::normalizing();
+        assert_eq!(cache.parse(&file, &p).unwrap().tasks.len(), 1);
+
+        // tamper with the entry to tell whether it is used
+        let entry_file = cache.entry_file(&fs::canonicalize(&file).unwrap());
+        let mut entry = read_entry(&entry_file).unwrap();
+        entry.parsed.tasks.clear();
+        cache.write_entry(&entry_file, &entry).unwrap();
+        assert_eq!(cache.parse(&file, &p).unwrap().tasks.len(), 0);
+
+        // another parser configuration does not use it
+        let other = MDLogParser::builder().task("LATER", crate::types::TaskState::Todo);
+        assert_eq!(cache.parse(&file, &other.build()).unwrap().tasks.len(), 1);
+
+        // neither does a changed file
+        fs::write(&file, "## Mon, 14.10.2019\n- TODO: a\n- TODO: b\n").unwrap();
+        assert_eq!(cache.parse(&file, &p).unwrap().tasks.len(), 2);
+
+        // broken entries are replaced
+        fs::write(&entry_file, "{").unwrap();
+        assert_eq!(cache.parse(&file, &p).unwrap().tasks.len(), 2);
+        assert!(read_entry(&entry_file).is_some());
+
+        cache.clear().unwrap();
+        assert!(!cache.dir().exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,297 @@
+use crate::timesheet;
+use crate::types::{Event, Task, TaskState};
+use chrono::naive::{NaiveDate, NaiveTime};
+
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// The columns available for events
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum EventColumn {
+    Date,
+    /// The time of the event
+    Start,
+    /// The end of the event, see [timesheet::event_end]
+    End,
+    /// The minutes between start and end, see [timesheet::duration]
+    Duration,
+    Message,
+    /// The tags of the event separated by spaces
+    Tags,
+}
+
+/// The columns available for tasks
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum TaskColumn {
+    /// The date of the day the task is listed under
+    Created,
+    /// The date of the ```due:``` marker of the task
+    Due,
+    /// The date the task was done on
+    Done,
+    /// The days from creation until the task was done
+    LeadTime,
+    State,
+    Priority,
+    Message,
+    /// The number of subtasks
+    Subtasks,
+    /// The number of closed subtasks
+    SubtasksDone,
+    /// The tags of the task separated by spaces
+    Tags,
+}
+
+/// All event columns in their default order
+pub const EVENT_COLUMNS: &[EventColumn] = &[
+    EventColumn::Date,
+    EventColumn::Start,
+    EventColumn::End,
+    EventColumn::Duration,
+    EventColumn::Message,
+    EventColumn::Tags,
+];
+
+/// All task columns in their default order
+pub const TASK_COLUMNS: &[TaskColumn] = &[
+    TaskColumn::Created,
+    TaskColumn::Due,
+    TaskColumn::Done,
+    TaskColumn::LeadTime,
+    TaskColumn::State,
+    TaskColumn::Priority,
+    TaskColumn::Message,
+    TaskColumn::Subtasks,
+    TaskColumn::SubtasksDone,
+    TaskColumn::Tags,
+];
+
+impl EventColumn {
+    /// The name of the column in the header
+    pub fn name(self) -> &'static str {
+        match self {
+            EventColumn::Date => "date",
+            EventColumn::Start => "start",
+            EventColumn::End => "end",
+            EventColumn::Duration => "duration",
+            EventColumn::Message => "message",
+            EventColumn::Tags => "tags",
+        }
+    }
+}
+
+impl FromStr for EventColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EVENT_COLUMNS
+            .iter()
+            .find(|c| c.name() == s)
+            .cloned()
+            .ok_or_else(|| format!("Unknown event column {}", s))
+    }
+}
+
+impl TaskColumn {
+    /// The name of the column in the header
+    pub fn name(self) -> &'static str {
+        match self {
+            TaskColumn::Created => "created",
+            TaskColumn::Due => "due",
+            TaskColumn::Done => "done",
+            TaskColumn::LeadTime => "lead_time",
+            TaskColumn::State => "state",
+            TaskColumn::Priority => "priority",
+            TaskColumn::Message => "message",
+            TaskColumn::Subtasks => "subtasks",
+            TaskColumn::SubtasksDone => "subtasks_done",
+            TaskColumn::Tags => "tags",
+        }
+    }
+}
+
+impl FromStr for TaskColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TASK_COLUMNS
+            .iter()
+            .find(|c| c.name() == s)
+            .cloned()
+            .ok_or_else(|| format!("Unknown task column {}", s))
+    }
+}
+
+/// Write the given events as CSV with a header line.  
+/// Events are expected in the order they appear in the log, as returned by the parser.
+///
+/// # Example:
+/// ```
+/// use mdlog::csv::{write_events, EventColumn};
+/// use mdlog::parser::MDLogParser;
+///
+/// let log = "## Mon, 14.10.2019\n- EVT 09:00: standup #team\n- EVT 09:15: coding\n";
+/// let events = MDLogParser::normalizing().parse_events(log).unwrap();
+///
+/// let columns = [EventColumn::Start, EventColumn::Duration, EventColumn::Tags];
+/// let mut out = vec![];
+/// write_events(&mut out, &events, &columns, ';').unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "start;duration;tags\n09:00;15;team\n09:15;;\n"
+/// );
+/// ```
+pub fn write_events<W: Write>(
+    out: &mut W,
+    events: &[Event],
+    columns: &[EventColumn],
+    delimiter: char,
+) -> io::Result<()> {
+    let header: Vec<_> = columns.iter().map(|c| c.name().to_string()).collect();
+    write_record(out, &header, delimiter)?;
+
+    for (i, e) in events.iter().enumerate() {
+        let end = timesheet::event_end(events, i);
+        let record: Vec<_> = columns
+            .iter()
+            .map(|c| match c {
+                EventColumn::Date => e.date.to_string(),
+                EventColumn::Start => format_time(e.time),
+                EventColumn::End => format_time(end),
+                EventColumn::Duration => timesheet::duration(events, i)
+                    .map(|d| d.num_minutes().to_string())
+                    .unwrap_or_default(),
+                EventColumn::Message => e.msg.clone(),
+                EventColumn::Tags => e.tags().join(" "),
+            })
+            .collect();
+        write_record(out, &record, delimiter)?;
+    }
+    Ok(())
+}
+
+/// Write the given tasks as CSV with a header line
+pub fn write_tasks<W: Write>(
+    out: &mut W,
+    tasks: &[Task],
+    columns: &[TaskColumn],
+    delimiter: char,
+) -> io::Result<()> {
+    let header: Vec<_> = columns.iter().map(|c| c.name().to_string()).collect();
+    write_record(out, &header, delimiter)?;
+
+    for t in tasks {
+        let record: Vec<_> = columns
+            .iter()
+            .map(|c| match c {
+                TaskColumn::Created => t.date.to_string(),
+                TaskColumn::Due => t.due().map(|d| d.to_string()).unwrap_or_default(),
+                TaskColumn::Done => done_date(t).map(|d| d.to_string()).unwrap_or_default(),
+                TaskColumn::LeadTime => done_date(t)
+                    .map(|d| (d - t.date).num_days().to_string())
+                    .unwrap_or_default(),
+                TaskColumn::State => format!("{:?}", t.state),
+                TaskColumn::Priority => t.priority.clone().unwrap_or_default(),
+                TaskColumn::Message => t.msg.clone(),
+                TaskColumn::Subtasks => t.subtasks.len().to_string(),
+                TaskColumn::SubtasksDone => t
+                    .subtasks
+                    .iter()
+                    .filter(|st| st.state.is_closed())
+                    .count()
+                    .to_string(),
+                TaskColumn::Tags => t.tags().join(" "),
+            })
+            .collect();
+        write_record(out, &record, delimiter)?;
+    }
+    Ok(())
+}
+
+/// Done tasks without a completion date were done on the day they are listed under
+fn done_date(t: &Task) -> Option<NaiveDate> {
+    match t.state {
+        TaskState::Done => Some(t.completed.unwrap_or(t.date)),
+        _ => None,
+    }
+}
+
+fn format_time(time: Option<NaiveTime>) -> String {
+    time.map(|t| t.format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Write one line of CSV, quoting fields where necessary
+fn write_record<W: Write>(out: &mut W, fields: &[String], delimiter: char) -> io::Result<()> {
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|f| {
+            if f.contains(delimiter) || f.contains(['"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.clone()
+            }
+        })
+        .collect();
+    writeln!(out, "{}", fields.join(&delimiter.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::MDLogParser;
+
+    const EXAMPLE_DATA: &str = "## Mon, 14.10.2019
+- EVT 09:00: standup #team
+- EVT 10:30-11:00: review, \"mdlog\"
+- EVT: lunch
+- EVT 23:00-01:00: release
+- TODO A: write report due:2019-10-18 #work
+  - DONE: outline
+  - TODO: draft
+- DONE: fix bug
+- DONE 2019-10-16: deploy
+
+## Tue, 15.10.2019
+- EVT 11:00: call
+";
+
+    #[test]
+    fn events() {
+        let events = MDLogParser::normalizing()
+            .parse_events(EXAMPLE_DATA)
+            .unwrap();
+        let mut out = vec![];
+        write_events(&mut out, &events, EVENT_COLUMNS, ',').unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "date,start,end,duration,message,tags
+2019-10-14,09:00,10:30,90,standup #team,team
+2019-10-14,10:30,11:00,30,\"review, \"\"mdlog\"\"\",
+2019-10-14,,,,lunch,
+2019-10-14,23:00,01:00,,release,
+2019-10-15,11:00,,,call,
+"
+        );
+    }
+
+    #[test]
+    fn tasks() {
+        let tasks = MDLogParser::normalizing()
+            .parse_tasks(EXAMPLE_DATA)
+            .unwrap();
+        let mut out = vec![];
+        write_tasks(&mut out, &tasks, TASK_COLUMNS, '\t').unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "created\tdue\tdone\tlead_time\tstate\tpriority\tmessage\tsubtasks\tsubtasks_done\ttags
+2019-10-14\t2019-10-18\t\t\tTodo\tA\twrite report due:2019-10-18 #work\t2\t1\twork
+2019-10-14\t\t2019-10-14\t0\tDone\t\tfix bug\t0\t0\t
+2019-10-14\t\t2019-10-16\t2\tDone\t\tdeploy\t0\t0\t
+"
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use crate::logset::LogSet;
+use crate::stream::Item;
+use crate::types::{Entry, Event, Person, Task, Week};
+use serde::{Deserialize, Serialize};
+
+use std::io::{self, ErrorKind, Write};
+use std::str::FromStr;
+
+/// The version of the export schema.  
+/// It is bumped whenever fields are removed, renamed or change their meaning; new fields do not bump it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The formats logs can be exported to
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum ExportFormat {
+    /// One pretty printed JSON document
+    Json,
+    /// One YAML document
+    Yaml,
+    /// One JSON record per line, tagged with its ```type```
+    Ndjson,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ExportFormat::Json),
+            "yaml" => Ok(ExportFormat::Yaml),
+            "ndjson" => Ok(ExportFormat::Ndjson),
+            _ => Err(format!(
+                "Unknown export format {} (expected json, yaml or ndjson)",
+                s
+            )),
+        }
+    }
+}
+
+/// Everything parsed from a log along with the version of the schema.
+///
+/// Schema version 1, written as JSON:
+/// ```text
+/// {
+///   "schema_version": 1,
+///   "tasks": [{"msg", "subtasks": [{"msg", "state", "span"}], "notes", "date", "state", "priority", "completed", "span"}],
+///   "events": [{"msg", "notes", "date", "time", "end", "span"}],
+///   "entries": [{"msg", "notes", "date", "span"}],
+///   "weeks": [{"number", "start", "end", "days", "span"}],
+///   "people": [{"name", "birthday", "presents"}]
+/// }
+/// ```
+/// - dates are written as ```"2019-10-14"```, times as ```"16:25:00"``` or ```null```
+/// - states are one of ```"Todo"```, ```"Doing"```, ```"Waiting"```, ```"Done"```, ```"Cancelled"```
+/// - spans are ```{"file", "line", "column", "byte_range": {"start", "end"}}``` with 1-based lines and columns
+/// - birthdays are ```{"KnownYear": "1990-02-28"}``` or ```{"UnknownYear": [2, 28]}```
+///
+/// In ndjson every task, event, entry, week and person is written on its own line
+/// with an additional ```"type"``` field (```"task"```, ```"event"```, ...).
+/// The first line is ```{"type": "header", "schema_version": 1}```.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Export {
+    pub schema_version: u32,
+    pub tasks: Vec<Task>,
+    pub events: Vec<Event>,
+    pub entries: Vec<Entry>,
+    pub weeks: Vec<Week>,
+    pub people: Vec<Person>,
+}
+
+/// Write items as ndjson as they are read, see [crate::stream::items].
+/// The output is the same as that of an [Export] without weeks.
+///
+/// # Example:
+/// ```
+/// use mdlog::export::write_ndjson_items;
+/// use mdlog::parser::MDLogParser;
+/// use mdlog::stream::items;
+///
+/// let log = "## Mon, 14.10.2019\n- TODO: a\n- EVT: b\n";
+/// let p = MDLogParser::normalizing();
+/// let mut out = vec![];
+/// write_ndjson_items(&mut out, items(&p, log.as_bytes()), &[]).unwrap();
+///
+/// let out = String::from_utf8(out).unwrap();
+/// assert_eq!(out.lines().count(), 3);
+/// assert!(out.lines().nth(2).unwrap().starts_with("{\"type\":\"event\""));
+/// ```
+pub fn write_ndjson_items<W, I>(out: &mut W, items: I, people: &[Person]) -> io::Result<()>
+where
+    W: Write,
+    I: Iterator<Item = io::Result<Item>>,
+{
+    write_record(
+        out,
+        &Record::Header {
+            schema_version: SCHEMA_VERSION,
+        },
+    )?;
+    for item in items {
+        let item = item?;
+        let record = match &item {
+            Item::Task(t) => Record::Task(t),
+            Item::Event(e) => Record::Event(e),
+            Item::Entry(e) => Record::Entry(e),
+        };
+        write_record(out, &record)?;
+    }
+    for p in people {
+        write_record(out, &Record::Person(p))?;
+    }
+    Ok(())
+}
+
+fn write_record<W: Write>(out: &mut W, record: &Record) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, record)?;
+    writeln!(out)
+}
+
+/// A single line of ndjson output
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record<'a> {
+    Header { schema_version: u32 },
+    Task(&'a Task),
+    Event(&'a Event),
+    Entry(&'a Entry),
+    Week(&'a Week),
+    Person(&'a Person),
+}
+
+impl Export {
+    /// Export everything in the given set along with the given people
+    pub fn new(set: &LogSet, people: Vec<Person>) -> Self {
+        Export {
+            schema_version: SCHEMA_VERSION,
+            tasks: set.tasks().to_vec(),
+            events: set.events().to_vec(),
+            entries: set.entries().to_vec(),
+            weeks: set.weeks().to_vec(),
+            people,
+        }
+    }
+
+    /// Write the export in the given format
+    pub fn write<W: Write>(&self, out: &mut W, format: ExportFormat) -> io::Result<()> {
+        match format {
+            ExportFormat::Json => {
+                serde_json::to_writer_pretty(&mut *out, self)?;
+                writeln!(out)
+            }
+            ExportFormat::Yaml => serde_yaml::to_writer(&mut *out, self)
+                .map_err(|e| io::Error::new(ErrorKind::InvalidData, e)),
+            ExportFormat::Ndjson => {
+                let header = Record::Header {
+                    schema_version: self.schema_version,
+                };
+                let mut records = std::iter::once(header)
+                    .chain(self.tasks.iter().map(Record::Task))
+                    .chain(self.events.iter().map(Record::Event))
+                    .chain(self.entries.iter().map(Record::Entry))
+                    .chain(self.weeks.iter().map(Record::Week))
+                    .chain(self.people.iter().map(Record::Person));
+                records.try_for_each(|r| write_record(out, &r))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::{self, MDLogParser};
+
+    const EXAMPLE_DATA: &str = "## Mon, 14.10.2019
+- a
+- EVT 16:25: b
+- TODO: c
+  - DONE: c1
+";
+
+    fn export() -> Export {
+        let mut set = LogSet::default();
+        set.add_str("log.md", EXAMPLE_DATA, &MDLogParser::normalizing())
+            .unwrap();
+
+        let people = parser::parse_people("Jane: 28.02.?\n").unwrap();
+        Export::new(&set, people)
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let export = export();
+        let mut out = vec![];
+        export.write(&mut out, ExportFormat::Json).unwrap();
+
+        let parsed: Export = serde_json::from_slice(&out).unwrap();
+        assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+        assert_eq!(parsed.tasks, export.tasks);
+        assert_eq!(parsed.events, export.events);
+        assert_eq!(parsed.entries, export.entries);
+        assert_eq!(parsed.people, export.people);
+        assert_eq!(parsed.tasks[0].span.line, 4);
+
+        let mut yaml = vec![];
+        export.write(&mut yaml, ExportFormat::Yaml).unwrap();
+        let parsed: Export = serde_yaml::from_slice(&yaml).unwrap();
+        assert_eq!(parsed.tasks, export.tasks);
+    }
+
+    #[test]
+    fn ndjson_records() {
+        let mut out = vec![];
+        export().write(&mut out, ExportFormat::Ndjson).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let types: Vec<_> = out
+            .lines()
+            .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+            .map(|v| v["type"].as_str().unwrap().to_owned())
+            .collect();
+        assert_eq!(types, ["header", "task", "event", "entry", "person"]);
+        assert!(out.starts_with("{\"type\":\"header\",\"schema_version\":1}\n"));
+    }
+}
@@ -0,0 +1,184 @@
+use chrono::naive::NaiveDate;
+use chrono::{Datelike, Duration, Weekday};
+use serde::{Deserialize, Serialize};
+
+use std::io::{self, ErrorKind};
+use std::str::FromStr;
+
+/// Languages weekday names can be written in
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+pub enum Locale {
+    /// Mon, Tue, ...
+    #[default]
+    English,
+    /// Mo, Di, ...
+    German,
+}
+
+impl Locale {
+    /// The short weekday names starting on monday
+    pub fn weekday_names(self) -> [&'static str; 7] {
+        match self {
+            Self::English => ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+            Self::German => ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+        }
+    }
+
+    pub fn weekday_name(self, weekday: Weekday) -> &'static str {
+        self.weekday_names()[weekday.num_days_from_monday() as usize]
+    }
+
+    /// Look up the weekday written as the given name
+    pub fn weekday(self, name: &str) -> Option<Weekday> {
+        let mut wd = Weekday::Mon;
+        for n in self.weekday_names().iter() {
+            if n.eq_ignore_ascii_case(name) {
+                return Some(wd);
+            }
+            wd = wd.succ();
+        }
+        None
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "en" => Ok(Self::English),
+            "de" => Ok(Self::German),
+            _ => Err(format!("Unknown locale '{}'; use en or de", s)),
+        }
+    }
+}
+
+/// How dates are written in day and week headings.  
+/// Shared by the parser and the generator so generated logs always parse back.
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct LogFormat {
+    /// The date pattern in strftime syntax, e.g. ```%d.%m.%Y``` or ```%Y-%m-%d```
+    pub date_pattern: String,
+    /// The language of the weekday names
+    pub locale: Locale,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        Self::new("%d.%m.%Y", Locale::English)
+    }
+}
+
+impl LogFormat {
+    pub fn new(date_pattern: &str, locale: Locale) -> Self {
+        Self {
+            date_pattern: date_pattern.to_owned(),
+            locale,
+        }
+    }
+
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        date.format(&self.date_pattern).to_string()
+    }
+
+    /// The text of a day heading, e.g. ```Mon, 14.10.2019```
+    pub fn day_heading(&self, date: NaiveDate) -> String {
+        format!(
+            "{}, {}",
+            self.locale.weekday_name(date.weekday()),
+            self.format_date(date)
+        )
+    }
+
+    /// The text of a week heading following the week tag, e.g. ```42, 14.10.2019 - 20.10.2019```
+    pub fn week_heading(&self, date: NaiveDate) -> String {
+        let start = date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+        let end = start + Duration::days(6);
+        format!(
+            "{}, {} - {}",
+            date.iso_week().week(),
+            self.format_date(start),
+            self.format_date(end)
+        )
+    }
+
+    /// Parse the text of a week heading following the week tag into its number, start and end
+    ///
+    /// # Example:
+    /// ```
+    /// # extern crate chrono;
+    /// # fn main(){
+    /// use mdlog::format::LogFormat;
+    /// use chrono::naive::NaiveDate;
+    ///
+    /// let (number, start, end) = LogFormat::default()
+    ///     .parse_week_heading("42, 14.10.2019 - 20.10.2019")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(number, 42);
+    /// assert_eq!(start, NaiveDate::from_ymd(2019, 10, 14));
+    /// assert_eq!(end, NaiveDate::from_ymd(2019, 10, 20));
+    /// # }
+    /// ```
+    pub fn parse_week_heading(&self, heading: &str) -> io::Result<(u32, NaiveDate, NaiveDate)> {
+        let invalid = |msg: String| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("Parsing '{}' failed with {}", heading, msg),
+            )
+        };
+
+        let (number, range) = heading
+            .split_once(", ")
+            .ok_or_else(|| invalid("a missing date range".into()))?;
+        let (start, end) = range
+            .split_once(" - ")
+            .ok_or_else(|| invalid("a missing end date".into()))?;
+
+        let number = u32::from_str(number.trim()).map_err(|e| invalid(e.to_string()))?;
+        let parse = |d: &str| {
+            NaiveDate::parse_from_str(d.trim(), &self.date_pattern)
+                .map_err(|e| invalid(e.to_string()))
+        };
+
+        Ok((number, parse(start)?, parse(end)?))
+    }
+
+    /// Parse the text of a day heading; the weekday name is optional and not checked.
+    ///
+    /// # Example:
+    /// ```
+    /// # extern crate chrono;
+    /// # fn main(){
+    /// use mdlog::format::{Locale, LogFormat};
+    /// use chrono::naive::NaiveDate;
+    ///
+    /// let de = LogFormat::new("%d.%m.%Y", Locale::German);
+    /// let iso = LogFormat::new("%Y-%m-%d", Locale::English);
+    ///
+    /// assert_eq!(de.parse_day_heading("Mo, 14.10.2019").unwrap(), NaiveDate::from_ymd(2019, 10, 14));
+    /// assert_eq!(iso.parse_day_heading("2019-10-14").unwrap(), NaiveDate::from_ymd(2019, 10, 14));
+    /// assert!(iso.parse_day_heading("Mon, 14.10.2019").is_err());
+    /// # }
+    /// ```
+    pub fn parse_day_heading(&self, heading: &str) -> io::Result<NaiveDate> {
+        let invalid = |msg: String| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("Parsing '{}' failed with {}", heading, msg),
+            )
+        };
+
+        // the pattern may contain ", " itself so try the whole heading first
+        let heading = heading.trim();
+        NaiveDate::parse_from_str(heading, &self.date_pattern)
+            .or_else(|e| match heading.find(", ") {
+                Some(pos) => NaiveDate::parse_from_str(
+                    heading[pos + ", ".len()..].trim(),
+                    &self.date_pattern,
+                ),
+                None => Err(e),
+            })
+            .map_err(|e| invalid(e.to_string()))
+    }
+}
@@ -0,0 +1,340 @@
+use crate::parser::{self, tag, Tags, TaskStyle};
+
+use std::borrow::Cow;
+
+/// The indentation of one level of nested items in canonical logs
+pub const INDENT: &str = "  ";
+
+/// Bring the given log into canonical form without changing its content:
+/// - nested items are indented by [INDENT] per level, their notes one level deeper
+/// - event times are written as ```HH:MM``` or ```HH:MM-HH:MM```
+/// - trailing whitespace is removed, as are blank lines at the start and end
+/// - runs of blank lines are collapsed into one
+/// - week and day headings are preceded by a blank line
+/// - all lines end with the line end used most in the log
+///
+/// Lines in code blocks are left as they are.
+///
+/// # Example:
+/// ```
+/// use mdlog::formatter::canonicalize;
+/// use mdlog::parser::Tags;
+///
+/// let log = "## Mon, 14.10.2019\n- EVT 9:5: a\n    - TODO: a1  \n## Tue, 15.10.2019\n- b";
+/// let formatted = canonicalize(log, &Tags::default());
+///
+/// assert_eq!(
+///     formatted,
+///     "## Mon, 14.10.2019\n- EVT 09:05: a\n  - TODO: a1\n\n## Tue, 15.10.2019\n- b\n"
+/// );
+/// ```
+pub fn canonicalize(log_data: &str, tags: &Tags) -> String {
+    let line_end = parser::detect_line_end(log_data);
+    let mut out: Vec<String> = vec![];
+    let mut in_code = false;
+    // the original widths of the indentation of the current item and its parents
+    let mut indents = vec![0];
+
+    for (line, _) in lines(log_data) {
+        let is_fence = line.trim_start().starts_with(tag::CODE_FENCE);
+        if in_code || is_fence {
+            in_code ^= is_fence;
+            out.push(line.into());
+            continue;
+        }
+
+        let line = line.trim_end();
+        let content = line.trim_start();
+        let width = indent_width(&line[..line.len() - content.len()]);
+
+        if content.is_empty() {
+            indents.truncate(1);
+            // no blank lines at the start and none in a row
+            if out.last().map(|l| !l.is_empty()).unwrap_or(false) {
+                out.push(String::new());
+            }
+        } else if line.starts_with(tag::DAY) || line.starts_with(tag::WEEK) {
+            indents.truncate(1);
+            if out.last().map(|l| !l.is_empty()).unwrap_or(false) {
+                out.push(String::new());
+            }
+            out.push(line.into());
+        } else if let Some(item) = tags.strip_item(content) {
+            while indents.len() > 1 && indents[indents.len() - 1] >= width {
+                indents.pop();
+            }
+            if width > 0 {
+                indents.push(width);
+            } else {
+                indents.truncate(1);
+            }
+            let marker = &content[..content.len() - item.len()];
+            let indent = INDENT.repeat(indents.len() - 1);
+            out.push(format!(
+                "{}{}{}",
+                indent,
+                marker,
+                canonical_item(item, tags)
+            ));
+        } else if width > 0 {
+            // notes belong to the item before them
+            out.push(format!("{}{}", INDENT.repeat(indents.len()), content));
+        } else {
+            out.push(content.into());
+        }
+    }
+
+    while out.last().map(|l| l.is_empty()).unwrap_or(false) {
+        out.pop();
+    }
+
+    let mut formatted = out.join(line_end);
+    if !formatted.is_empty() {
+        formatted.push_str(line_end);
+    }
+    formatted
+}
+
+/// Write the times of events as ```HH:MM```
+fn canonical_item<'a>(item: &'a str, tags: &Tags) -> Cow<'a, str> {
+    let keyword = match tags.event(item) {
+        Some(kw) => kw,
+        None => return Cow::Borrowed(item),
+    };
+    match parser::event_msg(&item[keyword.len()..]) {
+        Some((msg, Some(time), end)) => Cow::Owned(
+            format!("{} {}: {}", keyword, parser::event_time(time, end), msg)
+                .trim_end()
+                .to_owned(),
+        ),
+        _ => Cow::Borrowed(item),
+    }
+}
+
+/// The width of the given indentation with tabs counting as four spaces
+fn indent_width(indent: &str) -> usize {
+    indent.chars().map(|c| if c == '\t' { 4 } else { 1 }).sum()
+}
+
+/// Rewrite all tasks in the given log to use the given style.  
+/// States without a tag in the target style (e.g. WAIT for checkboxes) are left as they are,
+/// so are tasks with a priority or completion date (e.g. ```TODO A1: a```) and lines in code blocks.
+///
+/// # Example:
+/// ```
+/// use mdlog::formatter::convert_task_style;
+/// use mdlog::parser::{Tags, TaskStyle};
+///
+/// let log = "## Mon, 14.10.2019\n- TODO: a\n  - DONE: a1\n- WAIT: b\n";
+/// let converted = convert_task_style(log, &Tags::default(), TaskStyle::Checkbox);
+///
+/// assert_eq!(converted, "## Mon, 14.10.2019\n- [ ] a\n  - [x] a1\n- WAIT: b\n");
+/// ```
+pub fn convert_task_style(log_data: &str, tags: &Tags, style: TaskStyle) -> String {
+    let mut out = String::with_capacity(log_data.len());
+    let mut in_code = false;
+
+    for (line, line_end) in lines(log_data) {
+        if line.trim_start().starts_with(tag::CODE_FENCE) {
+            in_code = !in_code;
+        }
+
+        if in_code {
+            out.push_str(line);
+        } else {
+            out.push_str(&convert_line(line, tags, style));
+        }
+        out.push_str(line_end);
+    }
+
+    out
+}
+
+fn convert_line<'a>(line: &'a str, tags: &Tags, style: TaskStyle) -> Cow<'a, str> {
+    let (indent, rest) = line.split_at(line.len() - line.trim_start().len());
+    let item = match tags.strip_item(rest) {
+        Some(item) => item,
+        None => return Cow::Borrowed(line),
+    };
+    let marker = &rest[..rest.len() - item.len()];
+
+    let task_tag = match tags.task_tag(item) {
+        // checkboxes have no place for a priority or completion date, see writer::item_line
+        Some(t) if t.style == TaskStyle::Keyword && t.priority(item).is_some() => {
+            return Cow::Borrowed(line)
+        }
+        Some(t) if t.style != style => t,
+        _ => return Cow::Borrowed(line),
+    };
+    let new_tag = match tags.tag_for(task_tag.state, style) {
+        Some(t) => t,
+        None => return Cow::Borrowed(line),
+    };
+
+    let body = item[task_tag.keyword.len()..].trim_start();
+    let body = match style {
+        // keep everything but the ':' following the keyword
+        TaskStyle::Checkbox => body.strip_prefix(':').unwrap_or(body).trim_start(),
+        TaskStyle::Keyword => body,
+    };
+    let sep = match style {
+        TaskStyle::Checkbox => " ",
+        TaskStyle::Keyword => ": ",
+    };
+
+    Cow::Owned(
+        format!("{}{}{}{}{}", indent, marker, new_tag, sep, body)
+            .trim_end()
+            .to_owned(),
+    )
+}
+
+/// Split the data into lines along with their line ends
+fn lines(s: &str) -> impl Iterator<Item = (&str, &str)> {
+    s.split_inclusive('\n').map(|l| {
+        let content = l.trim_end_matches(['\n', '\r']);
+        (content, &l[content.len()..])
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::MDLogParser;
+
+    const UNFORMATTED_DATA: &str = "
+
+# Week 42, 14.10.2019 - 20.10.2019
+## Mon, 14.10.2019   
+- TODO: a
+    - DONE: a1
+        - TODO: a11
+    - DONE: a2
+      a note
+- EVT 9:5: meeting
+- EVT 9:5 - 9:45: with end
+- EVT: no time
+\t- [ ] tabbed
+```
+    - kept   
+```
+
+
+## Tue, 15.10.2019
+- b
+
+
+
+";
+
+    #[test]
+    fn canonical_format() {
+        let tags = Tags::default();
+        let formatted = canonicalize(UNFORMATTED_DATA, &tags);
+
+        assert_eq!(
+            formatted,
+            "# Week 42, 14.10.2019 - 20.10.2019
+
+## Mon, 14.10.2019
+- TODO: a
+  - DONE: a1
+    - TODO: a11
+  - DONE: a2
+    a note
+- EVT 09:05: meeting
+- EVT 09:05-09:45: with end
+- EVT: no time
+  - [ ] tabbed
+```
+    - kept   
+```
+
+## Tue, 15.10.2019
+- b
+"
+        );
+        assert_eq!(canonicalize(&formatted, &tags), formatted);
+
+        let p = MDLogParser::normalizing();
+        assert_eq!(
+            p.parse_tasks(UNFORMATTED_DATA).unwrap(),
+            p.parse_tasks(&formatted).unwrap()
+        );
+        assert_eq!(
+            p.parse_events(UNFORMATTED_DATA).unwrap(),
+            p.parse_events(&formatted).unwrap()
+        );
+
+        let windows = UNFORMATTED_DATA.replace('\n', "\r\n");
+        assert_eq!(
+            canonicalize(&windows, &tags),
+            formatted.replace('\n', "\r\n")
+        );
+    }
+
+    const EXAMPLE_DATA: &str = "
+## Mon, 14.10.2019
+- TODO A1: a
+  - DONE: a1
+  - [ ] a2
+- DOING: b
+```
+- TODO: code
+```
+- [X] c
+- DONE 2019-10-18: d: with colon
+
+";
+
+    #[test]
+    fn task_styles_round_trip() {
+        let tags = Tags::default();
+        let checkboxes = convert_task_style(EXAMPLE_DATA, &tags, TaskStyle::Checkbox);
+        let keywords = convert_task_style(&checkboxes, &tags, TaskStyle::Keyword);
+
+        assert_eq!(
+            checkboxes,
+            "
+## Mon, 14.10.2019
+- TODO A1: a
+  - [x] a1
+  - [ ] a2
+- DOING: b
+```
+- TODO: code
+```
+- [X] c
+- DONE 2019-10-18: d: with colon
+
+"
+        );
+        assert_eq!(
+            keywords,
+            "
+## Mon, 14.10.2019
+- TODO A1: a
+  - DONE: a1
+  - TODO: a2
+- DOING: b
+```
+- TODO: code
+```
+- DONE: c
+- DONE 2019-10-18: d: with colon
+
+"
+        );
+
+        let p = MDLogParser::from_line_end("\n");
+        let parsed = |s: &str| {
+            p.parse_tasks(s)
+                .unwrap()
+                .into_iter()
+                .map(|t| (t.msg, t.state, t.priority, t.completed, t.subtasks))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(parsed(EXAMPLE_DATA), parsed(&checkboxes));
+        assert_eq!(parsed(EXAMPLE_DATA), parsed(&keywords));
+    }
+}
@@ -0,0 +1,373 @@
+use crate::format::LogFormat;
+use crate::types::{Task, TaskState};
+use chrono::naive::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+
+/// The share of words two messages need to have in common to be taken for the same task
+pub const SIMILARITY: f64 = 0.75;
+
+/// A task as it was copied from day to day, in order of the days it is listed under
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct TaskHistory {
+    pub tasks: Vec<Task>,
+}
+
+impl TaskHistory {
+    /// The identity given by the first ```id:``` marker of any appearance
+    pub fn id(&self) -> Option<&str> {
+        self.tasks.iter().find_map(Task::id)
+    }
+
+    /// The first appearance of the task
+    pub fn first(&self) -> &Task {
+        &self.tasks[0]
+    }
+
+    /// The latest appearance of the task
+    pub fn last(&self) -> &Task {
+        &self.tasks[self.tasks.len() - 1]
+    }
+
+    /// All appearances after the first one
+    pub fn carry_overs(&self) -> &[Task] {
+        &self.tasks[1..]
+    }
+
+    /// The date the task was done on, if it was.
+    /// Done tasks without a completion date were done on the day they are last listed under.
+    pub fn completed(&self) -> Option<NaiveDate> {
+        let last = self.last();
+        match last.state {
+            TaskState::Done => Some(last.completed.unwrap_or(last.date)),
+            _ => None,
+        }
+    }
+
+    /// The task as one: its latest appearance, dated by its first one
+    pub fn task(&self) -> Task {
+        let completed = self
+            .completed()
+            .filter(|c| *c != self.first().date || self.last().completed.is_some());
+        Task {
+            date: self.first().date,
+            completed,
+            ..self.last().clone()
+        }
+    }
+
+    /// A few lines listing the first appearance, the carry-overs and the completion for humans
+    pub fn summary(&self, format: &LogFormat) -> String {
+        let location = |t: &Task| match &t.span.file {
+            Some(file) => format!("  ({}:{})", file.display(), t.span.line),
+            None => format!("  (line {})", t.span.line),
+        };
+
+        let mut lines = vec![self.last().msg.clone()];
+        for (i, t) in self.tasks.iter().enumerate() {
+            let what = if i == 0 { "created" } else { "carried over" };
+            lines.push(format!(
+                "  {}: {} as {:?}{}",
+                format.day_heading(t.date),
+                what,
+                t.state,
+                location(t)
+            ));
+        }
+        if let Some(d) = self.completed() {
+            lines.push(format!("  {}: done", format.day_heading(d)));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Group tasks copied from day to day into histories, in order of their first appearance.
+/// A task continues the history with the same ```id:``` marker or else the open history
+/// listed on an earlier day whose message is most similar (see [SIMILARITY]).
+/// Tags and markers are not compared, so they can be added along the way.
+/// Tasks without any other words are only continued by id.
+///
+/// # Example:
+/// ```
+/// use mdlog::history::histories;
+/// use mdlog::parser::MDLogParser;
+///
+/// let log = [
+///     "## Mon, 14.10.2019",
+///     "- TODO: write the report",
+///     "- TODO: call mom",
+///     "## Tue, 15.10.2019",
+///     "- TODO: write the report #work",
+///     "## Wed, 16.10.2019",
+///     "- DONE: Write the report #work",
+/// ]
+/// .join("\n");
+/// let tasks = MDLogParser::normalizing().parse_tasks(&log).unwrap();
+/// let histories = histories(&tasks);
+///
+/// assert_eq!(histories.len(), 2);
+/// assert_eq!(histories[0].carry_overs().len(), 2);
+/// assert_eq!(histories[0].task().lead_time(), Some(2));
+/// assert_eq!(histories[1].first().msg, "call mom");
+/// ```
+pub fn histories(tasks: &[Task]) -> Vec<TaskHistory> {
+    let mut sorted: Vec<&Task> = tasks.iter().collect();
+    sorted.sort_by_key(|t| t.date);
+    let words = Words::new(&sorted);
+
+    let mut histories: Vec<TaskHistory> = vec![];
+    // the index in sorted of the latest task of each history
+    let mut latest: Vec<usize> = vec![];
+    let mut by_id: HashMap<&str, usize> = HashMap::new();
+    // the histories whose latest task has the word among its rarest words
+    let mut by_word: Vec<Vec<usize>> = vec![vec![]; words.count];
+
+    for (i, t) in sorted.iter().enumerate() {
+        let found = t.id().and_then(|id| by_id.get(id).cloned()).or_else(|| {
+            let mut candidates = vec![];
+            for w in words.rarest(i) {
+                // closed histories are only continued by id, and the latest task of
+                // a history may no longer have the word among its rarest words
+                by_word[*w].retain(|h| {
+                    !histories[*h].last().state.is_closed() && words.rarest(latest[*h]).contains(w)
+                });
+                candidates.extend_from_slice(&by_word[*w]);
+            }
+            candidates.sort_unstable();
+            candidates.dedup();
+            candidates
+                .into_iter()
+                .filter(|h| continues(&histories[*h], t))
+                .map(|h| (h, words.similarity(i, latest[h])))
+                .filter(|(_, s)| *s >= SIMILARITY)
+                // later histories win ties, they are more likely to be copied from
+                .fold(None, |best: Option<(usize, f64)>, (h, s)| match best {
+                    Some((_, b)) if b > s => best,
+                    _ => Some((h, s)),
+                })
+                .map(|(h, _)| h)
+        });
+
+        let h = match found {
+            Some(h) => {
+                histories[h].tasks.push((*t).clone());
+                latest[h] = i;
+                h
+            }
+            None => {
+                histories.push(TaskHistory {
+                    tasks: vec![(*t).clone()],
+                });
+                latest.push(i);
+                histories.len() - 1
+            }
+        };
+        if let Some(id) = t.id() {
+            by_id.entry(id).or_insert(h);
+        }
+        for w in words.rarest(i) {
+            if !by_word[*w].contains(&h) {
+                by_word[*w].push(h);
+            }
+        }
+    }
+    histories
+}
+
+/// Every task once, see [TaskHistory::task]
+pub fn collapse(tasks: &[Task]) -> Vec<Task> {
+    histories(tasks).iter().map(TaskHistory::task).collect()
+}
+
+/// The words of the messages of tasks, numbered from the rarest to the most frequent word.
+/// Messages similar enough share at least one of their rarest words, so only messages
+/// sharing one of those need to be compared.
+struct Words {
+    /// The numbers of the words of each message in ascending order, i.e. rarest first
+    messages: Vec<Vec<usize>>,
+    count: usize,
+}
+
+impl Words {
+    fn new(tasks: &[&Task]) -> Self {
+        let words: Vec<_> = tasks.iter().map(|t| words(&t.msg)).collect();
+        let mut frequency: HashMap<&str, usize> = HashMap::new();
+        for w in words.iter().flatten() {
+            *frequency.entry(w).or_default() += 1;
+        }
+        let mut by_rarity: Vec<_> = frequency.iter().map(|(w, n)| (*n, *w)).collect();
+        by_rarity.sort_unstable();
+        let numbers: HashMap<&str, usize> = by_rarity
+            .iter()
+            .enumerate()
+            .map(|(i, (_, w))| (*w, i))
+            .collect();
+
+        let messages = words
+            .iter()
+            .map(|ws| {
+                let mut m: Vec<usize> = ws.iter().map(|w| numbers[w.as_str()]).collect();
+                m.sort_unstable();
+                m
+            })
+            .collect();
+        Words {
+            messages,
+            count: numbers.len(),
+        }
+    }
+
+    /// The words of the message any message at least [SIMILARITY] similar shares one of
+    fn rarest(&self, message: usize) -> &[usize] {
+        let m = &self.messages[message];
+        let n = m.len();
+        if n == 0 {
+            return &[];
+        }
+        &m[..n + 1 - (SIMILARITY * n as f64).ceil() as usize]
+    }
+
+    /// The share of words in both messages of all the words
+    fn similarity(&self, a: usize, b: usize) -> f64 {
+        let (a, b) = (&self.messages[a], &self.messages[b]);
+        let (mut i, mut j, mut both) = (0, 0, 0);
+        while i < a.len() && j < b.len() {
+            match a[i].cmp(&b[j]) {
+                Ordering::Less => i += 1,
+                Ordering::Greater => j += 1,
+                Ordering::Equal => {
+                    both += 1;
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        let all = a.len() + b.len() - both;
+        if all == 0 {
+            return 1.0;
+        }
+        both as f64 / all as f64
+    }
+}
+
+/// Whether the task may continue the history: it is still open, listed on an earlier day
+/// and has no other ```id:```
+fn continues(history: &TaskHistory, task: &Task) -> bool {
+    let last = history.last();
+    let other_id = history.id().is_some() && task.id().is_some() && history.id() != task.id();
+    !last.state.is_closed() && last.date < task.date && !other_id
+}
+
+/// The lowercase words of a message without tags, markers and punctuation
+fn words(msg: &str) -> BTreeSet<String> {
+    msg.split_whitespace()
+        .filter(|w| !w.starts_with('#'))
+        .filter(|w| {
+            !w.split_once(':')
+                .map(|(_, v)| !v.is_empty())
+                .unwrap_or(false)
+        })
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::{MDLogParser, Tags};
+    use chrono::Datelike;
+
+    const EXAMPLE_DATA: &str = "## Mon, 14.10.2019
+- TODO: weekly review
+- TODO: fix bug in parser id:parser
+- TODO: write report due:2019-10-18
+
+## Tue, 15.10.2019
+- DONE: weekly review
+- DOING: fix the parser bug id:parser
+- TODO: write report
+
+## Wed, 16.10.2019
+- TODO: write report
+- TODO: fix bug in lexer
+
+## Mon, 21.10.2019
+- TODO: weekly review
+- DONE 2019-10-22: write report
+";
+
+    #[test]
+    fn carry_overs() {
+        let tasks = MDLogParser::builder()
+            .normalize(true)
+            .tags(Tags::extended())
+            .build()
+            .parse_tasks(EXAMPLE_DATA)
+            .unwrap();
+        let histories = histories(&tasks);
+        let day = |d| NaiveDate::from_ymd(2019, 10, d);
+
+        let summary: Vec<_> = histories
+            .iter()
+            .map(|h| {
+                let dates: Vec<_> = h.tasks.iter().map(|t| t.date.day()).collect();
+                (h.first().msg.as_str(), dates, h.completed())
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("weekly review", vec![14, 15], Some(day(15))),
+                ("fix bug in parser id:parser", vec![14, 15], None),
+                (
+                    "write report due:2019-10-18",
+                    vec![14, 15, 16, 21],
+                    Some(day(22))
+                ),
+                ("fix bug in lexer", vec![16], None),
+                // the review was done already, so this is a new one
+                ("weekly review", vec![21], None),
+            ]
+        );
+
+        let collapsed = collapse(&tasks);
+        assert_eq!(collapsed.len(), 5);
+        assert_eq!(collapsed[0].date, day(14));
+        assert_eq!(collapsed[0].lead_time(), Some(1));
+        assert_eq!(collapsed[1].state, TaskState::Doing);
+        assert_eq!(collapsed[2].lead_time(), Some(8));
+
+        let summary = histories[0].summary(&LogFormat::default());
+        assert_eq!(
+            summary,
+            "weekly review
+  Mon, 14.10.2019: created as Todo  (line 2)
+  Tue, 15.10.2019: carried over as Done  (line 7)
+  Tue, 15.10.2019: done"
+        );
+    }
+
+    #[test]
+    fn without_words() {
+        let log = "## Mon, 14.10.2019
+- TODO: #work
+- TODO: #work id:w
+
+## Tue, 15.10.2019
+- TODO: #work
+- DONE: #work id:w
+";
+        let tasks = MDLogParser::normalizing().parse_tasks(log).unwrap();
+        let dates: Vec<_> = histories(&tasks)
+            .iter()
+            .map(|h| h.tasks.iter().map(|t| t.date.day()).collect::<Vec<_>>())
+            .collect();
+        assert_eq!(dates, vec![vec![14], vec![14, 15], vec![15]]);
+    }
+}
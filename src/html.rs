@@ -0,0 +1,330 @@
+use crate::format::LogFormat;
+use crate::history;
+use crate::logset::LogSet;
+use crate::types::{Event, Task};
+use chrono::naive::NaiveDate;
+use chrono::{Datelike, Duration};
+
+use std::collections::BTreeSet;
+
+/// Inlined into every page so the pages need no other resources
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+nav a { margin-right: 1em; }
+table.calendar { border-collapse: collapse; width: 100%; table-layout: fixed; }
+table.calendar th, table.calendar td { border: 1px solid #ccc; vertical-align: top; padding: 0.3em; }
+table.calendar td { height: 6em; }
+td.other { background: #f4f4f4; }
+ul { margin: 0.2em 0; padding-left: 1.2em; }
+.board { display: flex; gap: 2em; }
+.board section { flex: 1; }
+.state { font-size: small; font-weight: bold; color: #555; }
+.closed { color: #888; text-decoration: line-through; }
+small { color: #888; }
+";
+
+/// A rendered HTML page
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Page {
+    /// The file name of the page; pages link to each other by it
+    pub name: String,
+    pub html: String,
+}
+
+/// Render the set as static HTML pages:
+/// - ```index.html``` with a calendar of every month containing items
+/// - ```tasks.html``` with a board of open and closed tasks
+/// - one page per week, e.g. ```week-2019-42.html```, listing all items day by day
+pub fn render(set: &LogSet, format: &LogFormat) -> Vec<Page> {
+    let mut pages = vec![
+        Page {
+            name: "index.html".into(),
+            html: page("Calendar", &calendar(set, format)),
+        },
+        Page {
+            name: "tasks.html".into(),
+            html: page("Tasks", &task_board(set, format)),
+        },
+    ];
+
+    let weeks: BTreeSet<_> = dates(set).into_iter().map(monday).collect();
+    for start in weeks {
+        let week = start.iso_week();
+        let title = format!("Week {}, {}", week.week(), week.year());
+        pages.push(Page {
+            name: week_page_name(start),
+            html: page(&title, &week_body(set, format, start)),
+        });
+    }
+    pages
+}
+
+/// Replace the characters with a special meaning in HTML
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title}</title>
+<style>{style}</style>
+</head>
+<body>
+<nav><a href=\"index.html\">Calendar</a><a href=\"tasks.html\">Tasks</a></nav>
+<h1>{title}</h1>
+{body}
+</body>
+</html>
+",
+        title = escape(title),
+        style = STYLE,
+        body = body
+    )
+}
+
+/// All dates with items or day headings
+fn dates(set: &LogSet) -> BTreeSet<NaiveDate> {
+    let mut dates: BTreeSet<_> = set.tasks().iter().map(|t| t.date).collect();
+    dates.extend(set.events().iter().map(|e| e.date));
+    dates.extend(set.entries().iter().map(|e| e.date));
+    dates.extend(set.weeks().iter().flat_map(|w| w.days.iter().cloned()));
+    dates
+}
+
+fn monday(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+}
+
+fn week_page_name(date: NaiveDate) -> String {
+    let week = date.iso_week();
+    format!("week-{}-{:02}.html", week.year(), week.week())
+}
+
+fn calendar(set: &LogSet, format: &LogFormat) -> String {
+    let dates = dates(set);
+    let (first, last) = match (dates.iter().next(), dates.iter().next_back()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return "<p>Nothing logged yet.</p>".into(),
+    };
+
+    let mut html = String::new();
+    let mut month = NaiveDate::from_ymd(first.year(), first.month(), 1);
+    while month <= last {
+        html.push_str(&format!("<h2>{}</h2>\n", month.format("%B %Y")));
+        html.push_str("<table class=\"calendar\">\n<tr>");
+        for name in format.locale.weekday_names().iter() {
+            html.push_str(&format!("<th>{}</th>", name));
+        }
+        html.push_str("</tr>\n");
+
+        let mut day = monday(month);
+        while day.month() == month.month() || day < month {
+            html.push_str("<tr>");
+            for _ in 0..7 {
+                if day.month() == month.month() {
+                    html.push_str(&calendar_cell(set, day));
+                } else {
+                    html.push_str("<td class=\"other\"></td>");
+                }
+                day = day.succ();
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+
+        month = day.with_day(1).unwrap_or(day);
+    }
+    html
+}
+
+fn calendar_cell(set: &LogSet, day: NaiveDate) -> String {
+    let mut html = format!("<td><a href=\"{}\">{}</a>", week_page_name(day), day.day());
+
+    let events: Vec<_> = set.events().iter().filter(|e| e.date == day).collect();
+    if !events.is_empty() {
+        html.push_str("<ul>");
+        for e in events {
+            html.push_str(&format!("<li>{}</li>", event_title(e)));
+        }
+        html.push_str("</ul>");
+    }
+
+    let tasks = set.tasks().iter().filter(|t| t.date == day).count();
+    if tasks > 0 {
+        html.push_str(&format!("<small>{} tasks</small>", tasks));
+    }
+    html.push_str("</td>");
+    html
+}
+
+fn task_board(set: &LogSet, format: &LogFormat) -> String {
+    // tasks copied from day to day are shown once, dated by their first day
+    let tasks = history::collapse(set.tasks());
+    let column = |title: &str, closed: bool| {
+        let mut html = format!("<section>\n<h2>{}</h2>\n<ul>\n", title);
+        for t in tasks.iter().filter(|t| t.state.is_closed() == closed) {
+            html.push_str(&format!(
+                "<li>{} <small><a href=\"{}\">{}</a></small></li>\n",
+                task_title(t),
+                week_page_name(t.date),
+                escape(&format.format_date(t.date))
+            ));
+        }
+        html.push_str("</ul>\n</section>\n");
+        html
+    };
+
+    format!(
+        "<div class=\"board\">\n{}{}</div>",
+        column("Open", false),
+        column("Done", true)
+    )
+}
+
+fn week_body(set: &LogSet, format: &LogFormat, start: NaiveDate) -> String {
+    let mut html = String::new();
+    for day in (0..7).map(|d| start + Duration::days(d)) {
+        let events: Vec<_> = set.events().iter().filter(|e| e.date == day).collect();
+        let tasks: Vec<_> = set.tasks().iter().filter(|t| t.date == day).collect();
+        let entries: Vec<_> = set.entries().iter().filter(|e| e.date == day).collect();
+        if events.is_empty() && tasks.is_empty() && entries.is_empty() {
+            continue;
+        }
+
+        html.push_str(&format!(
+            "<h2>{}</h2>\n<ul>\n",
+            escape(&format.day_heading(day))
+        ));
+        for e in events {
+            html.push_str(&format!("<li>{}{}</li>\n", event_title(e), notes(&e.notes)));
+        }
+        for t in tasks {
+            html.push_str(&format!("<li>{}", task_title(t)));
+            if !t.subtasks.is_empty() {
+                html.push_str("<ul>");
+                for st in &t.subtasks {
+                    html.push_str(&format!(
+                        "<li{}><span class=\"state\">{:?}</span> {}</li>",
+                        if st.state.is_closed() {
+                            " class=\"closed\""
+                        } else {
+                            ""
+                        },
+                        st.state,
+                        escape(&st.msg)
+                    ));
+                }
+                html.push_str("</ul>");
+            }
+            html.push_str(&notes(&t.notes));
+            html.push_str("</li>\n");
+        }
+        for e in entries {
+            html.push_str(&format!("<li>{}{}</li>\n", escape(&e.msg), notes(&e.notes)));
+        }
+        html.push_str("</ul>\n");
+    }
+    html
+}
+
+fn event_title(e: &Event) -> String {
+    match e.time {
+        Some(t) => format!("{} {}", t.format("%H:%M"), escape(&e.msg)),
+        None => escape(&e.msg),
+    }
+}
+
+fn task_title(t: &Task) -> String {
+    let mut html = format!(
+        "<span class=\"state\">{:?}</span> {}",
+        t.state,
+        escape(&t.msg)
+    );
+    if !t.subtasks.is_empty() {
+        let closed = t.subtasks.iter().filter(|st| st.state.is_closed()).count();
+        html.push_str(&format!(
+            " <small>({}/{})</small>",
+            closed,
+            t.subtasks.len()
+        ));
+    }
+    html
+}
+
+fn notes(notes: &[String]) -> String {
+    if notes.is_empty() {
+        return String::new();
+    }
+    let items: Vec<_> = notes
+        .iter()
+        .map(|n| format!("<li>{}</li>", escape(n)))
+        .collect();
+    format!("<ul>{}</ul>", items.concat())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::MDLogParser;
+
+    const EXAMPLE_DATA: &str = "## Mon, 14.10.2019
+- EVT 16:25: <b> & co
+- TODO: c
+  - DONE: c1
+  - TODO: c2
+
+## Fri, 01.11.2019
+- DONE: d
+- some entry
+";
+
+    #[test]
+    fn pages() {
+        let mut set = LogSet::default();
+        set.add_str("log.md", EXAMPLE_DATA, &MDLogParser::normalizing())
+            .unwrap();
+
+        let pages = render(&set, &LogFormat::default());
+        let names: Vec<_> = pages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "index.html",
+                "tasks.html",
+                "week-2019-42.html",
+                "week-2019-44.html"
+            ]
+        );
+
+        let calendar = &pages[0].html;
+        assert!(calendar.contains("<h2>October 2019</h2>"));
+        assert!(calendar.contains("<h2>November 2019</h2>"));
+        assert!(calendar.contains("16:25 &lt;b&gt; &amp; co"));
+        assert!(!calendar.contains("<h2>December 2019</h2>"));
+        assert!(!calendar.contains("http"));
+
+        let board = &pages[1].html;
+        let (open, done) = board.split_at(board.find("<h2>Done</h2>").unwrap());
+        assert!(open.contains("Todo</span> c <small>(1/2)</small>"));
+        assert!(done.contains("Done</span> d"));
+
+        let week = &pages[3].html;
+        assert!(week.contains("<h2>Fri, 01.11.2019</h2>"));
+        assert!(week.contains("<li>some entry</li>"));
+    }
+}
@@ -1,15 +1,55 @@
 extern crate chrono;
+extern crate glob;
 extern crate serde;
+extern crate serde_json;
 extern crate serde_yaml;
 
+/// Caching parsed logs on disk
+pub mod cache;
+/// Exporting events and tasks as CSV
+pub mod csv;
+/// Exporting parsed logs as JSON or YAML
+pub mod export;
+/// Date and heading formats of MDLog files
+pub mod format;
+/// Rewriting MDLog files
+pub mod formatter;
+/// Tracking tasks copied over several days
+pub mod history;
+/// Rendering logs as static HTML pages
+pub mod html;
+/// Checking MDLog files for inconsistencies
+pub mod lint;
+/// Loading logs split over several files
+pub mod logset;
+/// Converting logs from and to Org files
+pub mod org;
 /// Parsers for MDLog related formats
 pub mod parser;
 
+/// Full-text search over the items of logs
+pub mod search;
+/// Statistics over tasks and events
+pub mod stats;
+/// Reading logs line by line
+pub mod stream;
+/// Time spent on events per tag
+pub mod timesheet;
+/// Converting tasks from and to todo.txt
+pub mod todotxt;
+/// Writing items into MDLog files
+pub mod writer;
+
 /// Types representing MDLog data
 pub mod types {
     use chrono::naive::{NaiveDate, NaiveTime};
-    use chrono::Datelike;
+    use chrono::{Datelike, Duration, Weekday};
     use serde::{Deserialize, Serialize};
+    use std::fmt;
+    use std::hash::{Hash, Hasher};
+    use std::ops::Range;
+    use std::path::PathBuf;
+    use std::str::FromStr;
 
     #[derive(Serialize, Hash, Clone, Deserialize, Eq, PartialEq, Debug)]
     pub struct Person {
@@ -18,35 +58,224 @@ pub mod types {
         pub presents: Option<Vec<String>>,
     }
 
+    /// A date recurring every year; used for birthdays and all other occasions
     #[derive(Serialize, Hash, Clone, Deserialize, Eq, PartialEq, Debug)]
     pub enum Birthday {
         /// Full Date
         KnownYear(NaiveDate),
-        /// Year, Month
+        /// Month, Day
         UnknownYear(u32, u32),
     }
 
+    /// Where to celebrate dates on the 29th of February in non-leap years
+    #[derive(Serialize, Hash, Clone, Copy, Deserialize, Eq, PartialEq, Debug, Default)]
+    pub enum LeapDayPolicy {
+        /// Celebrate on the 28th of February
+        #[default]
+        Feb28,
+        /// Celebrate on the 1st of March
+        Mar1,
+    }
+
+    /// The kinds of recurring occasions besides plain birthdays
+    #[derive(Serialize, Hash, Clone, Copy, Deserialize, Eq, PartialEq, Debug)]
+    #[serde(rename_all = "lowercase")]
+    pub enum OccasionKind {
+        Birthday,
+        Wedding,
+        Work,
+        NameDay,
+        Memorial,
+    }
+
+    /// A recurring occasion, e.g. a wedding anniversary or a memorial date
     #[derive(Serialize, Hash, Clone, Deserialize, Eq, PartialEq, Debug)]
+    pub struct Occasion {
+        pub kind: OccasionKind,
+        pub date: Birthday,
+        pub label: Option<String>,
+        /// The name of the person this occasion belongs to
+        pub person: Option<String>,
+    }
+
+    /// Where a parsed item is located in its source.  
+    /// Not part of the equality or hash of the item.
+    #[derive(Serialize, Hash, Clone, Deserialize, Eq, PartialEq, Debug, Default)]
+    pub struct Span {
+        /// The file the item was loaded from (if any)
+        pub file: Option<PathBuf>,
+        /// The 1-based line the item starts on
+        pub line: usize,
+        /// The 1-based column (in chars) of the item marker
+        pub column: usize,
+        /// The bytes of the input covered by the item including its subtasks and notes
+        pub byte_range: Range<usize>,
+    }
+
+    #[derive(Serialize, Clone, Deserialize, Debug)]
     pub struct Task {
         pub msg: String,
         pub subtasks: Vec<Subtask>,
         pub notes: Vec<String>,
         pub date: NaiveDate,
-        pub is_done: bool,
+        pub state: TaskState,
+        /// The word between the keyword and the ':', e.g. ```A1``` in ```TODO A1: x```
+        #[serde(default)]
+        pub priority: Option<String>,
+        /// The date a closed task was closed on,
+        /// given as in ```DONE 2019-10-18: x``` or by a ```done:2019-10-18``` marker
+        #[serde(default)]
+        pub completed: Option<NaiveDate>,
+        #[serde(default)]
+        pub span: Span,
     }
 
-    #[derive(Serialize, Hash, Clone, Deserialize, Eq, PartialEq, Debug)]
+    #[derive(Serialize, Clone, Deserialize, Debug)]
     pub struct Subtask {
         pub msg: String,
-        pub is_done: bool,
+        pub state: TaskState,
+        #[serde(default)]
+        pub span: Span,
     }
 
-    #[derive(Serialize, Hash, Clone, Deserialize, Eq, PartialEq, Debug)]
+    /// The states a task can be in
+    #[derive(Serialize, Hash, Clone, Copy, Deserialize, Eq, PartialEq, Debug)]
+    pub enum TaskState {
+        Todo,
+        Doing,
+        Waiting,
+        Done,
+        Cancelled,
+    }
+
+    #[derive(Serialize, Clone, Deserialize, Debug)]
     pub struct Event {
         pub msg: String,
         pub notes: Vec<String>,
         pub date: NaiveDate,
         pub time: Option<NaiveTime>,
+        /// The end of the event if given as in ```EVT 09:00-10:30: x```
+        #[serde(default)]
+        pub end: Option<NaiveTime>,
+        #[serde(default)]
+        pub span: Span,
+    }
+
+    /// A top-level item which is neither a task nor an event
+    #[derive(Serialize, Clone, Deserialize, Debug)]
+    pub struct Entry {
+        pub msg: String,
+        pub notes: Vec<String>,
+        pub date: NaiveDate,
+        #[serde(default)]
+        pub span: Span,
+    }
+
+    /// A week heading along with the days below it
+    #[derive(Serialize, Clone, Deserialize, Debug)]
+    pub struct Week {
+        pub number: u32,
+        pub start: NaiveDate,
+        pub end: NaiveDate,
+        /// The dates of the day headings below the week heading in order of appearance
+        pub days: Vec<NaiveDate>,
+        #[serde(default)]
+        pub span: Span,
+    }
+
+    /// Inconsistencies between a week heading, the ISO calendar and its days
+    #[derive(Serialize, Hash, Clone, Deserialize, Eq, PartialEq, Debug)]
+    pub enum WeekProblem {
+        /// The start of the week is not a monday
+        StartNotMonday,
+        /// The end is not the sunday following the start
+        WrongEnd { expected: NaiveDate },
+        /// The number does not match the ISO week of the start
+        WrongNumber { expected: u32 },
+        /// A day heading is outside of the range of the week
+        DayOutside(NaiveDate),
+    }
+
+    // items are compared and hashed without their span
+    // so the same item parsed from different places is still equal
+
+    impl PartialEq for Task {
+        fn eq(&self, other: &Self) -> bool {
+            self.msg == other.msg
+                && self.subtasks == other.subtasks
+                && self.notes == other.notes
+                && self.date == other.date
+                && self.state == other.state
+                && self.priority == other.priority
+                && self.completed == other.completed
+        }
+    }
+
+    impl Eq for Task {}
+
+    impl Hash for Task {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.msg.hash(state);
+            self.subtasks.hash(state);
+            self.notes.hash(state);
+            self.date.hash(state);
+            self.state.hash(state);
+            self.priority.hash(state);
+            self.completed.hash(state);
+        }
+    }
+
+    impl PartialEq for Subtask {
+        fn eq(&self, other: &Self) -> bool {
+            self.msg == other.msg && self.state == other.state
+        }
+    }
+
+    impl Eq for Subtask {}
+
+    impl Hash for Subtask {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.msg.hash(state);
+            self.state.hash(state);
+        }
+    }
+
+    impl PartialEq for Event {
+        fn eq(&self, other: &Self) -> bool {
+            self.msg == other.msg
+                && self.notes == other.notes
+                && self.date == other.date
+                && self.time == other.time
+                && self.end == other.end
+        }
+    }
+
+    impl Eq for Event {}
+
+    impl Hash for Event {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.msg.hash(state);
+            self.notes.hash(state);
+            self.date.hash(state);
+            self.time.hash(state);
+            self.end.hash(state);
+        }
+    }
+
+    impl PartialEq for Entry {
+        fn eq(&self, other: &Self) -> bool {
+            self.msg == other.msg && self.notes == other.notes && self.date == other.date
+        }
+    }
+
+    impl Eq for Entry {}
+
+    impl Hash for Entry {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.msg.hash(state);
+            self.notes.hash(state);
+            self.date.hash(state);
+        }
     }
 
     impl Birthday {
@@ -63,6 +292,420 @@ pub mod types {
                 Self::UnknownYear(m, _) => *m,
             }
         }
+
+        pub fn year(&self) -> Option<i32> {
+            match self {
+                Self::KnownYear(d) => Some(d.year()),
+                Self::UnknownYear(_, _) => None,
+            }
+        }
+
+        /// The date this recurs on in the given year.  
+        /// Leap-day dates are moved according to the policy in non-leap years.
+        /// Returns None if month and day do not form a valid date.
+        ///
+        /// # Example:
+        /// ```
+        /// # extern crate chrono;
+        /// # fn main(){
+        /// use mdlog::types::{Birthday, LeapDayPolicy};
+        /// use chrono::naive::NaiveDate;
+        ///
+        /// let bd = Birthday::KnownYear(NaiveDate::from_ymd(2000, 2, 29));
+        ///
+        /// assert_eq!(bd.in_year(2019, LeapDayPolicy::Feb28), Some(NaiveDate::from_ymd(2019, 2, 28)));
+        /// assert_eq!(bd.in_year(2019, LeapDayPolicy::Mar1), Some(NaiveDate::from_ymd(2019, 3, 1)));
+        /// assert_eq!(bd.in_year(2020, LeapDayPolicy::Mar1), Some(NaiveDate::from_ymd(2020, 2, 29)));
+        /// # }
+        /// ```
+        pub fn in_year(&self, year: i32, policy: LeapDayPolicy) -> Option<NaiveDate> {
+            let (m, d) = (self.month(), self.day());
+            NaiveDate::from_ymd_opt(year, m, d).or_else(|| match (m, d, policy) {
+                (2, 29, LeapDayPolicy::Feb28) => NaiveDate::from_ymd_opt(year, 2, 28),
+                (2, 29, LeapDayPolicy::Mar1) => NaiveDate::from_ymd_opt(year, 3, 1),
+                _ => None,
+            })
+        }
+
+        /// Whether this recurs on the given date
+        pub fn falls_on(&self, date: NaiveDate, policy: LeapDayPolicy) -> bool {
+            self.in_year(date.year(), policy) == Some(date)
+        }
+
+        /// The number of full years passed on the given date; None if the year is unknown.  
+        /// The policy decides when leap-day dates are passed in non-leap years.
+        ///
+        /// # Example:
+        /// ```
+        /// # extern crate chrono;
+        /// # fn main(){
+        /// use mdlog::types::{Birthday, LeapDayPolicy};
+        /// use chrono::naive::NaiveDate;
+        ///
+        /// let bd = Birthday::KnownYear(NaiveDate::from_ymd(1990, 1, 5));
+        ///
+        /// assert_eq!(bd.age_on(NaiveDate::from_ymd(2019, 12, 30), LeapDayPolicy::Feb28), Some(29));
+        /// assert_eq!(bd.age_on(NaiveDate::from_ymd(2020, 1, 5), LeapDayPolicy::Feb28), Some(30));
+        /// assert_eq!(Birthday::UnknownYear(1, 5).age_on(NaiveDate::from_ymd(2020, 1, 5), LeapDayPolicy::Feb28), None);
+        /// # }
+        /// ```
+        pub fn age_on(&self, date: NaiveDate, policy: LeapDayPolicy) -> Option<i32> {
+            let year = self.year()?;
+            let passed = self
+                .in_year(date.year(), policy)
+                .map(|d| d <= date)
+                .unwrap_or(false);
+            Some(date.year() - year - if passed { 0 } else { 1 })
+        }
+
+        /// The first recurrence on or after the given date
+        pub fn next_occurrence(&self, from: NaiveDate, policy: LeapDayPolicy) -> Option<NaiveDate> {
+            self.in_year(from.year(), policy)
+                .filter(|d| *d >= from)
+                .or_else(|| self.in_year(from.year() + 1, policy))
+        }
+    }
+
+    impl Week {
+        /// Whether the given date is within the range of this week
+        pub fn contains(&self, date: NaiveDate) -> bool {
+            self.start <= date && date <= self.end
+        }
+
+        /// Check the heading against the ISO calendar and the contained day headings
+        pub fn validate(&self) -> Vec<WeekProblem> {
+            let mut problems = vec![];
+            if self.start.weekday() != Weekday::Mon {
+                problems.push(WeekProblem::StartNotMonday);
+            }
+            let expected = self.start + Duration::days(6);
+            if self.end != expected {
+                problems.push(WeekProblem::WrongEnd { expected });
+            }
+            let expected = self.start.iso_week().week();
+            if self.number != expected {
+                problems.push(WeekProblem::WrongNumber { expected });
+            }
+            problems.extend(
+                self.days
+                    .iter()
+                    .filter(|d| !self.contains(**d))
+                    .map(|d| WeekProblem::DayOutside(*d)),
+            );
+            problems
+        }
+
+        /// The tasks created within this week
+        pub fn tasks<'a>(&'a self, tasks: &'a [Task]) -> impl Iterator<Item = &'a Task> + 'a {
+            tasks.iter().filter(move |t| self.contains(t.date))
+        }
+
+        /// The events taking place within this week
+        pub fn events<'a>(&'a self, events: &'a [Event]) -> impl Iterator<Item = &'a Event> + 'a {
+            events.iter().filter(move |e| self.contains(e.date))
+        }
+    }
+
+    impl PartialEq for Week {
+        fn eq(&self, other: &Self) -> bool {
+            self.number == other.number
+                && self.start == other.start
+                && self.end == other.end
+                && self.days == other.days
+        }
+    }
+
+    impl Eq for Week {}
+
+    impl Hash for Week {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.number.hash(state);
+            self.start.hash(state);
+            self.end.hash(state);
+            self.days.hash(state);
+        }
+    }
+
+    impl TaskState {
+        pub fn is_done(self) -> bool {
+            self == Self::Done
+        }
+
+        /// Whether nothing is left to do, i.e. the task is done or cancelled
+        pub fn is_closed(self) -> bool {
+            self == Self::Done || self == Self::Cancelled
+        }
+    }
+
+    impl Task {
+        /// The tags in the message of this task, see [tags]
+        pub fn tags(&self) -> Vec<&str> {
+            tags(&self.msg)
+        }
+
+        /// The date given by a ```due:2019-10-20``` marker in the message of this task
+        pub fn due(&self) -> Option<NaiveDate> {
+            marker_date(&self.msg, "due:")
+        }
+
+        /// The identity given by an ```id:report``` marker in the message of this task
+        pub fn id(&self) -> Option<&str> {
+            self.msg
+                .split_whitespace()
+                .filter_map(|w| w.strip_prefix(crate::parser::tag::ID_MARKER))
+                .find(|id| !id.is_empty())
+        }
+
+        /// The days from the day the task is listed under until it was completed
+        pub fn lead_time(&self) -> Option<i64> {
+            self.completed.map(|c| (c - self.date).num_days())
+        }
+    }
+
+    impl Event {
+        /// The tags in the message of this event, see [tags]
+        pub fn tags(&self) -> Vec<&str> {
+            tags(&self.msg)
+        }
+    }
+
+    /// The date of the first ```<key>2019-10-20``` marker in the given text
+    pub(crate) fn marker_date(text: &str, key: &str) -> Option<NaiveDate> {
+        text.split_whitespace()
+            .filter_map(|w| w.strip_prefix(key))
+            .find_map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    }
+
+    /// The tags in the given text, i.e. the words starting with ```#```, without the ```#```
+    ///
+    /// # Example:
+    /// ```
+    /// use mdlog::types::tags;
+    ///
+    /// assert_eq!(tags("review #mdlog, plan #q4-2019."), vec!["mdlog", "q4-2019"]);
+    /// ```
+    pub fn tags(text: &str) -> Vec<&str> {
+        text.split_whitespace()
+            .filter_map(|w| w.strip_prefix('#'))
+            .map(|t| t.trim_end_matches(|c: char| !c.is_alphanumeric()))
+            .filter(|t| !t.is_empty())
+            .collect()
+    }
+
+    impl Person {
+        /// The birthday of this person as an occasion
+        pub fn occasion(&self) -> Occasion {
+            Occasion {
+                kind: OccasionKind::Birthday,
+                date: self.birthday.clone(),
+                label: None,
+                person: Some(self.name.clone()),
+            }
+        }
+
+        /// The age of this person on the given date; None if the birth year is unknown
+        pub fn age_on(&self, date: NaiveDate, policy: LeapDayPolicy) -> Option<i32> {
+            self.birthday.age_on(date, policy)
+        }
     }
 
+    impl Occasion {
+        /// The label to print for this occasion; falls back to the linked person
+        pub fn title(&self) -> &str {
+            self.label
+                .as_ref()
+                .or(self.person.as_ref())
+                .map(|s| s.as_str())
+                .unwrap_or("")
+        }
+
+        /// The number of full years since the occasion on the given date; None if the year is unknown
+        pub fn years_on(&self, date: NaiveDate, policy: LeapDayPolicy) -> Option<i32> {
+            self.date.age_on(date, policy)
+        }
+
+        /// The first date the occasion recurs on at or after the given date
+        pub fn next_occurrence(&self, from: NaiveDate, policy: LeapDayPolicy) -> Option<NaiveDate> {
+            self.date.next_occurrence(from, policy)
+        }
+
+        /// Whether the occasion is a milestone (e.g. a 30th birthday) on the given date
+        pub fn is_milestone_on(&self, date: NaiveDate, policy: LeapDayPolicy) -> bool {
+            self.date.falls_on(date, policy)
+                && self
+                    .years_on(date, policy)
+                    .map(|y| self.kind.is_milestone(y))
+                    .unwrap_or(false)
+        }
+    }
+
+    /// The occasions recurring within the given number of days from the given date (inclusive),
+    /// ordered by the date they recur on
+    ///
+    /// # Example:
+    /// ```
+    /// # extern crate chrono;
+    /// # fn main(){
+    /// use mdlog::types::{upcoming, Birthday, LeapDayPolicy, Occasion, OccasionKind};
+    /// use chrono::naive::NaiveDate;
+    ///
+    /// let occasion = |label: &str, date| Occasion {
+    ///     kind: OccasionKind::Birthday,
+    ///     date,
+    ///     label: Some(label.into()),
+    ///     person: None,
+    /// };
+    /// let occasions = [
+    ///     occasion("Alex", Birthday::UnknownYear(1, 3)),
+    ///     occasion("Sam", Birthday::KnownYear(NaiveDate::from_ymd(1990, 12, 24))),
+    ///     occasion("Kim", Birthday::UnknownYear(6, 1)),
+    /// ];
+    ///
+    /// let from = NaiveDate::from_ymd(2019, 12, 20);
+    /// let next: Vec<_> = upcoming(&occasions, from, 14, LeapDayPolicy::Feb28)
+    ///     .into_iter()
+    ///     .map(|(d, o)| (d, o.title()))
+    ///     .collect();
+    ///
+    /// assert_eq!(next, vec![
+    ///     (NaiveDate::from_ymd(2019, 12, 24), "Sam"),
+    ///     (NaiveDate::from_ymd(2020, 1, 3), "Alex"),
+    /// ]);
+    /// # }
+    /// ```
+    pub fn upcoming(
+        occasions: &[Occasion],
+        from: NaiveDate,
+        days: u32,
+        policy: LeapDayPolicy,
+    ) -> Vec<(NaiveDate, &Occasion)> {
+        let until = from + Duration::days(i64::from(days));
+        let mut next: Vec<_> = occasions
+            .iter()
+            .filter_map(|o| o.next_occurrence(from, policy).map(|d| (d, o)))
+            .filter(|(d, _)| *d <= until)
+            .collect();
+        next.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.title().cmp(b.1.title())));
+        next
+    }
+
+    impl OccasionKind {
+        /// Whether reaching the given number of years is a milestone for this kind of occasion.  
+        /// Birthdays are milestones when coming of age and every decade, everything else every 5 years.
+        pub fn is_milestone(self, years: i32) -> bool {
+            match self {
+                Self::Birthday => years == 18 || (years > 0 && years % 10 == 0),
+                _ => years > 0 && years % 5 == 0,
+            }
+        }
+    }
+
+    impl FromStr for LeapDayPolicy {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "feb28" => Ok(Self::Feb28),
+                "mar1" => Ok(Self::Mar1),
+                _ => Err(format!(
+                    "Unknown leap day policy '{}'; use feb28 or mar1",
+                    s
+                )),
+            }
+        }
+    }
+
+    impl fmt::Display for WeekProblem {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Self::StartNotMonday => write!(f, "the week does not start on a monday"),
+                Self::WrongEnd { expected } => write!(f, "the week should end on {}", expected),
+                Self::WrongNumber { expected } => {
+                    write!(f, "the week should be week number {}", expected)
+                }
+                Self::DayOutside(d) => write!(f, "the day {} is outside of the week", d),
+            }
+        }
+    }
+
+    impl fmt::Display for OccasionKind {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let s = match self {
+                Self::Birthday => "Birthday",
+                Self::Wedding => "Anniversary",
+                Self::Work => "Work Anniversary",
+                Self::NameDay => "Name Day",
+                Self::Memorial => "Memorial",
+            };
+            f.write_str(s)
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+            NaiveDate::from_ymd(y, m, d)
+        }
+
+        #[test]
+        fn leap_day_policies() {
+            let known = Birthday::KnownYear(day(2000, 2, 29));
+            let unknown = Birthday::UnknownYear(2, 29);
+
+            for bd in &[&known, &unknown] {
+                assert!(bd.falls_on(day(2019, 2, 28), LeapDayPolicy::Feb28));
+                assert!(!bd.falls_on(day(2019, 3, 1), LeapDayPolicy::Feb28));
+                assert!(bd.falls_on(day(2019, 3, 1), LeapDayPolicy::Mar1));
+                assert!(!bd.falls_on(day(2019, 2, 28), LeapDayPolicy::Mar1));
+                // leap years are unaffected
+                assert!(bd.falls_on(day(2020, 2, 29), LeapDayPolicy::Mar1));
+                assert!(!bd.falls_on(day(2020, 3, 1), LeapDayPolicy::Mar1));
+            }
+
+            // the year passes on the day it is celebrated on
+            assert_eq!(
+                known.age_on(day(2019, 2, 28), LeapDayPolicy::Feb28),
+                Some(19)
+            );
+            assert_eq!(
+                known.age_on(day(2019, 2, 28), LeapDayPolicy::Mar1),
+                Some(18)
+            );
+            assert_eq!(known.age_on(day(2019, 3, 1), LeapDayPolicy::Mar1), Some(19));
+            assert_eq!(
+                known.age_on(day(2020, 2, 29), LeapDayPolicy::Mar1),
+                Some(20)
+            );
+        }
+
+        #[test]
+        fn next_occurrences() {
+            let leap = Birthday::UnknownYear(2, 29);
+            assert_eq!(
+                leap.next_occurrence(day(2019, 1, 10), LeapDayPolicy::Mar1),
+                Some(day(2019, 3, 1))
+            );
+            assert_eq!(
+                leap.next_occurrence(day(2019, 3, 2), LeapDayPolicy::Feb28),
+                Some(day(2020, 2, 29))
+            );
+
+            let bd = Birthday::KnownYear(day(1990, 10, 14));
+            assert_eq!(
+                bd.next_occurrence(day(2019, 10, 14), LeapDayPolicy::Feb28),
+                Some(day(2019, 10, 14))
+            );
+            assert_eq!(
+                bd.next_occurrence(day(2019, 10, 15), LeapDayPolicy::Feb28),
+                Some(day(2020, 10, 14))
+            );
+            // month and day not forming a date never occur
+            assert_eq!(
+                Birthday::UnknownYear(2, 30).next_occurrence(day(2019, 1, 1), LeapDayPolicy::Mar1),
+                None
+            );
+        }
+    }
 }
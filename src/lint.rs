@@ -0,0 +1,335 @@
+use crate::format::LogFormat;
+use crate::parser::{self, tag, MDLogParser, Tags};
+use crate::types::{Span, TaskState, Week, WeekProblem};
+use chrono::naive::NaiveDate;
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashSet;
+use std::fmt;
+
+/// The kinds of problems reported by the linter
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LintKind {
+    /// A week or day heading which can not be parsed
+    InvalidHeading,
+    /// A week heading not matching the ISO calendar
+    InvalidWeek,
+    DayOutOfOrder,
+    DuplicateDay,
+    /// The weekday name of a day heading does not match its date
+    WrongWeekday,
+    /// A day heading outside of the range of its week heading
+    DayOutsideWeek,
+    /// A task line with keywords for several states
+    ConflictingStates,
+    InvalidEventTime,
+    /// A done task with subtasks which are still open
+    DoneWithOpenSubtasks,
+    InconsistentIndentation,
+}
+
+/// A problem found by the linter
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Lint {
+    pub kind: LintKind,
+    pub message: String,
+    /// The offending line
+    pub span: Span,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.span.file {
+            write!(f, "{}:", file.display())?;
+        }
+        write!(
+            f,
+            "{}:{}: {}",
+            self.span.line, self.span.column, self.message
+        )
+    }
+}
+
+/// Check a log for inconsistencies the parser silently accepts.  
+/// Uses the tags and heading format of the given parser.
+///
+/// # Example:
+/// ```
+/// use mdlog::lint::{lint, LintKind};
+/// use mdlog::parser::MDLogParser;
+///
+/// let log = "## Tue, 14.10.2019\n- DONE: a\n  - TODO: a1\n";
+/// let lints = lint(&MDLogParser::normalizing(), log);
+///
+/// let kinds: Vec<_> = lints.iter().map(|l| (l.kind, l.span.line)).collect();
+/// assert_eq!(kinds, vec![(LintKind::WrongWeekday, 1), (LintKind::DoneWithOpenSubtasks, 2)]);
+/// ```
+pub fn lint(parser: &MDLogParser, log_data: &str) -> Vec<Lint> {
+    let mut linter = Linter {
+        tags: parser.tags(),
+        format: parser.format(),
+        lints: vec![],
+        week: None,
+        last_day: None,
+        seen_days: HashSet::new(),
+        indent_unit: None,
+        indents: vec![],
+        open_task: None,
+    };
+
+    let mut in_code = false;
+    for (number, (start, line)) in parser::lines(log_data).enumerate() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        let span = Span {
+            file: None,
+            line: number + 1,
+            column: line[..indent].chars().count() + 1,
+            byte_range: start + indent..start + line.len(),
+        };
+
+        if trimmed.starts_with(tag::CODE_FENCE) {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            linter.end_of_task();
+        } else if let Some(heading) = line.strip_prefix(tag::WEEK) {
+            linter.end_of_task();
+            linter.week_heading(heading, span);
+        } else if let Some(heading) = line.strip_prefix(tag::DAY) {
+            linter.end_of_task();
+            linter.day_heading(heading, span);
+        } else if let Some(item) = linter.tags.strip_item(trimmed) {
+            if line[..indent].contains('\t') {
+                linter.push(
+                    LintKind::InconsistentIndentation,
+                    "indented with tabs".into(),
+                    &span,
+                );
+            }
+            if indent == 0 {
+                linter.end_of_task();
+                linter.item(item, span);
+            } else {
+                linter.subitem(item, indent, span);
+            }
+        }
+    }
+    linter.end_of_task();
+
+    linter.lints
+}
+
+struct Linter<'a> {
+    tags: &'a Tags,
+    format: &'a LogFormat,
+    lints: Vec<Lint>,
+    /// the current week
+    week: Option<Week>,
+    last_day: Option<NaiveDate>,
+    seen_days: HashSet<NaiveDate>,
+    /// the number of spaces per level of indentation; taken from the first subitem
+    indent_unit: Option<usize>,
+    /// the indentation of the current item and its parents
+    indents: Vec<usize>,
+    /// the current top-level task, whether it is done and whether it has open subtasks
+    open_task: Option<(Span, bool, bool)>,
+}
+
+impl<'a> Linter<'a> {
+    fn push(&mut self, kind: LintKind, message: String, span: &Span) {
+        self.lints.push(Lint {
+            kind,
+            message,
+            span: span.clone(),
+        });
+    }
+
+    fn week_heading(&mut self, heading: &str, span: Span) {
+        let (number, start, end) = match self.format.parse_week_heading(heading) {
+            Ok(w) => w,
+            Err(e) => {
+                self.week = None;
+                return self.push(LintKind::InvalidHeading, e.to_string(), &span);
+            }
+        };
+
+        let week = Week {
+            number,
+            start,
+            end,
+            days: vec![],
+            span: span.clone(),
+        };
+        for problem in week.validate() {
+            self.push(LintKind::InvalidWeek, problem.to_string(), &span);
+        }
+        self.week = Some(week);
+    }
+
+    fn day_heading(&mut self, heading: &str, span: Span) {
+        let date = match self.format.parse_day_heading(heading) {
+            Ok(d) => d,
+            Err(e) => return self.push(LintKind::InvalidHeading, e.to_string(), &span),
+        };
+
+        if let Some((name, _)) = heading.split_once(", ") {
+            let expected = self.format.locale.weekday_name(date.weekday());
+            if self.format.locale.weekday(name.trim()) != Some(date.weekday()) {
+                let msg = format!("{} is a {} not a {}", date, expected, name.trim());
+                self.push(LintKind::WrongWeekday, msg, &span);
+            }
+        }
+
+        if !self.seen_days.insert(date) {
+            let msg = format!("the day {} appears more than once", date);
+            self.push(LintKind::DuplicateDay, msg, &span);
+        } else if let Some(last) = self.last_day.filter(|last| date < *last) {
+            let msg = format!("the day {} comes after {}", date, last);
+            self.push(LintKind::DayOutOfOrder, msg, &span);
+        }
+        self.last_day = Some(date);
+
+        let outside = self
+            .week
+            .as_ref()
+            .map(|w| !w.contains(date))
+            .unwrap_or(false);
+        if outside {
+            let msg = WeekProblem::DayOutside(date).to_string();
+            self.push(LintKind::DayOutsideWeek, msg, &span);
+        }
+    }
+
+    fn item(&mut self, item: &str, span: Span) {
+        self.indents = vec![0];
+
+        if let Some(t) = self.tags.task_tag(item) {
+            self.check_conflicts(item, &span);
+            self.open_task = Some((span, t.state == TaskState::Done, false));
+        } else if let Some(keyword) = self.tags.event(item) {
+            if !is_valid_event_time(&item[keyword.len()..]) {
+                let msg =
+                    "the time of the event is malformed (expected HH:MM or HH:MM-HH:MM)".into();
+                self.push(LintKind::InvalidEventTime, msg, &span);
+            }
+        }
+    }
+
+    fn subitem(&mut self, item: &str, indent: usize, span: Span) {
+        while self.indents.last().map(|i| *i >= indent).unwrap_or(false) {
+            self.indents.pop();
+        }
+        let depth = self.indents.len();
+        self.indents.push(indent);
+
+        // subitems without a top-level item are left to the parser
+        if let Some(unit) = indent.checked_div(depth) {
+            let unit = *self.indent_unit.get_or_insert(unit);
+            if indent != unit * depth {
+                let msg = format!("indented by {} spaces, expected {}", indent, unit * depth);
+                self.push(LintKind::InconsistentIndentation, msg, &span);
+            }
+        }
+
+        if let Some(t) = self.tags.task_tag(item) {
+            self.check_conflicts(item, &span);
+            if let Some((_, _, has_open)) = self.open_task.as_mut() {
+                *has_open |= !t.state.is_closed();
+            }
+        }
+    }
+
+    fn check_conflicts(&mut self, item: &str, span: &Span) {
+        if self.tags.is_conflicting(item) {
+            let msg = "the task has keywords for several states".into();
+            self.push(LintKind::ConflictingStates, msg, span);
+        }
+    }
+
+    fn end_of_task(&mut self) {
+        if let Some((span, true, true)) = self.open_task.take() {
+            let msg = "the task is done but has open subtasks".into();
+            self.push(LintKind::DoneWithOpenSubtasks, msg, &span);
+        }
+    }
+}
+
+/// Whether the time following an event keyword (if any) is valid and written as
+/// zero-padded ```HH:MM``` or ```HH:MM-HH:MM```
+fn is_valid_event_time(rest: &str) -> bool {
+    let (msg, time, end) = match parser::event_msg(rest) {
+        Some(e) => e,
+        None => return false,
+    };
+    let time = match time {
+        Some(t) => t,
+        None => return true,
+    };
+    // the message is what follows the time
+    let written: String = rest[..rest.len() - msg.len()]
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    written.trim_end_matches(':') == parser::event_time(time, end)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_DATA: &str = "# Week 42, 14.10.2019 - 20.10.2019
+
+## Mon, 14.10.2019
+- EVT 9:5: not padded
+- EVT 25:00: broken
+- TODO: a
+  - b
+    - c
+   - d
+
+## Wed, 16.10.2019
+- DONE TODO: a
+```
+## Mon, 01.01.2001
+```
+## Tue, 16.10.2019
+## Tue, 15.10.2019
+## Mon, 21.10.2019
+## Mon 21.10
+# Week 1, 14.10.2019 - 20.10.2019";
+
+    #[test]
+    fn lints() {
+        let lints = lint(&MDLogParser::normalizing(), EXAMPLE_DATA);
+        let kinds: Vec<_> = lints.iter().map(|l| (l.span.line, l.kind)).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (4, LintKind::InvalidEventTime),
+                (5, LintKind::InvalidEventTime),
+                (9, LintKind::InconsistentIndentation),
+                (12, LintKind::ConflictingStates),
+                (16, LintKind::WrongWeekday),
+                (16, LintKind::DuplicateDay),
+                (17, LintKind::DayOutOfOrder),
+                (18, LintKind::DayOutsideWeek),
+                (19, LintKind::InvalidHeading),
+                (20, LintKind::InvalidWeek),
+            ]
+        );
+        assert_eq!(lints[2].span.column, 4);
+        assert_eq!(lints[2].message, "indented by 3 spaces, expected 4");
+
+        let fine = "## Mon, 14.10.2019\n- EVT: a\n- EVT 09:05: b\n- EVT 09:05 - 10:30: c\n";
+        assert_eq!(lint(&MDLogParser::normalizing(), fine), vec![]);
+    }
+}
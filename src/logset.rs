@@ -0,0 +1,313 @@
+use crate::cache::ParseCache;
+use crate::parser::MDLogParser;
+use crate::stream::{self, Item};
+use crate::types::{Entry, Event, Task, Week};
+use glob::glob;
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::io::{self, BufRead, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// The file extension of MDLog files picked up from directories
+pub const EXTENSION: &str = "md";
+
+/// Everything parsed from a single log
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ParsedLog {
+    pub tasks: Vec<Task>,
+    pub events: Vec<Event>,
+    pub entries: Vec<Entry>,
+    pub weeks: Vec<Week>,
+}
+
+impl ParsedLog {
+    pub fn parse(log_data: &str, parser: &MDLogParser) -> io::Result<Self> {
+        Ok(ParsedLog {
+            tasks: parser.parse_tasks(log_data)?,
+            events: parser.parse_events(log_data)?,
+            entries: parser.parse_entries(log_data)?,
+            weeks: parser.parse_weeks(log_data)?,
+        })
+    }
+
+    /// Read the items of a log line by line, see [stream::items]; weeks are not read
+    pub fn read<R: BufRead>(reader: R, parser: &MDLogParser) -> io::Result<Self> {
+        let mut parsed = ParsedLog::default();
+        for item in stream::items(parser, reader) {
+            match item? {
+                Item::Task(t) => parsed.tasks.push(t),
+                Item::Event(e) => parsed.events.push(e),
+                Item::Entry(e) => parsed.entries.push(e),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// A number of MDLog files parsed and merged into one log, e.g. one file per month.  
+/// Tasks and events are sorted by date; items of the same date keep the order of the files.
+/// The span of every item refers to the file it came from.
+#[derive(Clone, Debug, Default)]
+pub struct LogSet {
+    files: Vec<PathBuf>,
+    tasks: Vec<Task>,
+    events: Vec<Event>,
+    entries: Vec<Entry>,
+    weeks: Vec<Week>,
+}
+
+impl LogSet {
+    /// Load a single file, all MDLog files in a directory (recursively) or all files matching a glob pattern
+    pub fn load(path: &str, parser: &MDLogParser) -> io::Result<Self> {
+        Self::load_files(&find_files(path)?, parser)
+    }
+
+    /// Load all the given files
+    pub fn load_files(files: &[PathBuf], parser: &MDLogParser) -> io::Result<Self> {
+        let mut set = Self::default();
+        for file in files {
+            set.extend(file, parse_file(file, parser)?);
+        }
+        set.sort();
+        Ok(set)
+    }
+
+    /// Load all the given files, taking the parse results from the cache where they are valid
+    pub fn load_files_cached(
+        files: &[PathBuf],
+        parser: &MDLogParser,
+        cache: &ParseCache,
+    ) -> io::Result<Self> {
+        let mut set = Self::default();
+        for file in files {
+            let parsed = cache.parse(file, parser).map_err(|e| with_file(file, e))?;
+            set.extend(file, parsed);
+        }
+        set.sort();
+        Ok(set)
+    }
+
+    /// Parse the given file and merge it into this set
+    pub fn add_file(&mut self, file: &Path, parser: &MDLogParser) -> io::Result<()> {
+        self.add_parsed(file, parse_file(file, parser)?);
+        Ok(())
+    }
+
+    /// Parse the given log data as if it was read from the named file and merge it into this set
+    ///
+    /// # Example:
+    /// ```
+    /// use mdlog::logset::LogSet;
+    /// use mdlog::parser::MDLogParser;
+    ///
+    /// let mut set = LogSet::default();
+    /// let p = MDLogParser::normalizing();
+    /// set.add_str("2019-11.md", "## Fri, 01.11.2019\n- TODO: b\n", &p).unwrap();
+    /// set.add_str("2019-10.md", "## Mon, 14.10.2019\n- TODO: a\n", &p).unwrap();
+    ///
+    /// assert_eq!(set.tasks()[0].msg, "a");
+    /// assert_eq!(set.tasks()[1].span.file.as_ref().unwrap().to_str(), Some("2019-11.md"));
+    /// ```
+    pub fn add_str(&mut self, name: &str, log_data: &str, parser: &MDLogParser) -> io::Result<()> {
+        let file = Path::new(name);
+        let parsed = ParsedLog::parse(log_data, parser).map_err(|e| with_file(file, e))?;
+        self.add_parsed(file, parsed);
+        Ok(())
+    }
+
+    /// Merge the items parsed from the given file into this set
+    pub fn add_parsed(&mut self, file: &Path, parsed: ParsedLog) {
+        self.extend(file, parsed);
+        self.sort();
+    }
+
+    /// Append the items parsed from the given file, leaving the set unsorted
+    fn extend(&mut self, file: &Path, parsed: ParsedLog) {
+        let ParsedLog {
+            mut tasks,
+            mut events,
+            mut entries,
+            mut weeks,
+        } = parsed;
+
+        for t in tasks.iter_mut() {
+            t.span.file = Some(file.to_owned());
+            for st in t.subtasks.iter_mut() {
+                st.span.file = Some(file.to_owned());
+            }
+        }
+        for e in events.iter_mut() {
+            e.span.file = Some(file.to_owned());
+        }
+        for e in entries.iter_mut() {
+            e.span.file = Some(file.to_owned());
+        }
+        for w in weeks.iter_mut() {
+            w.span.file = Some(file.to_owned());
+        }
+
+        self.tasks.extend(tasks);
+        self.events.extend(events);
+        self.entries.extend(entries);
+        self.weeks.extend(weeks);
+        self.files.push(file.to_owned());
+    }
+
+    fn sort(&mut self) {
+        // stable sorts so items on the same date stay in file order
+        self.tasks.sort_by_key(|t| t.date);
+        self.events.sort_by_key(|e| (e.date, e.time));
+        self.entries.sort_by_key(|e| e.date);
+        self.weeks.sort_by_key(|w| w.start);
+    }
+
+    /// The files this set was loaded from
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn weeks(&self) -> &[Week] {
+        &self.weeks
+    }
+}
+
+fn parse_file(file: &Path, parser: &MDLogParser) -> io::Result<ParsedLog> {
+    fs::read_to_string(file)
+        .and_then(|log_data| ParsedLog::parse(&log_data, parser))
+        .map_err(|e| with_file(file, e))
+}
+
+fn with_file(file: &Path, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {}", file.display(), e))
+}
+
+/// Resolve a path to the MDLog files it stands for.  
+/// Files are taken as they are, directories are searched recursively for ```.md``` files
+/// (not following links to directories) and anything else is treated as a glob pattern.
+/// The result is sorted by path.
+pub fn find_files(path: &str) -> io::Result<Vec<PathBuf>> {
+    let p = Path::new(path);
+    let mut files = if p.is_file() {
+        vec![p.to_owned()]
+    } else if p.is_dir() {
+        let mut files = vec![];
+        find_in_dir(p, &mut files)?;
+        files
+    } else {
+        let paths =
+            glob(path).map_err(|e| io::Error::new(ErrorKind::InvalidInput, e.to_string()))?;
+        paths
+            .map(|p| p.map_err(io::Error::from))
+            .collect::<io::Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|p| p.is_file())
+            .collect()
+    };
+
+    if files.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            format!("No MDLog files found for {}", path),
+        ));
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+fn find_in_dir(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        // links to directories may form cycles
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            find_in_dir(&path, files)?;
+        } else if file_type.is_symlink() && path.is_dir() {
+            continue;
+        } else if path.extension().map(|e| e == EXTENSION).unwrap_or(false) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::naive::NaiveDate;
+    use std::env;
+
+    #[test]
+    fn load_directory() {
+        let dir = env::temp_dir().join(format!("mdlog-logset-{}", std::process::id()));
+        fs::create_dir_all(dir.join("2019")).unwrap();
+        fs::write(
+            dir.join("2019").join("2019-11.md"),
+            "## Fri, 01.11.2019\n- TODO: b\n- EVT 10:00: e\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("2019-10.md"),
+            "\n## Mon, 14.10.2019\n- some entry\n- TODO: a\n",
+        )
+        .unwrap();
+        fs::write(dir.join("notes.txt"), "\n## Mon, 14.10.2019\n- TODO: x\n").unwrap();
+
+        let p = MDLogParser::normalizing();
+        let set = LogSet::load(dir.to_str().unwrap(), &p).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(set.files().len(), 2);
+        assert_eq!(set.entries().len(), 1);
+
+        let tasks: Vec<_> = set
+            .tasks()
+            .iter()
+            .map(|t| {
+                let file = t.span.file.as_ref().unwrap();
+                (
+                    file.file_name().unwrap().to_str().unwrap(),
+                    t.span.line,
+                    t.date,
+                )
+            })
+            .collect();
+        assert_eq!(
+            tasks,
+            vec![
+                ("2019-10.md", 4, NaiveDate::from_ymd(2019, 10, 14)),
+                ("2019-11.md", 2, NaiveDate::from_ymd(2019, 11, 1)),
+            ]
+        );
+        assert_eq!(set.events()[0].span.line, 3);
+        assert_eq!(set.events()[0].msg, "e");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn directory_links() {
+        let dir = env::temp_dir().join(format!("mdlog-logset-links-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("2019-10.md"), "## Mon, 14.10.2019\n- TODO: a\n").unwrap();
+        // a link back to the directory itself
+        std::os::unix::fs::symlink(&dir, dir.join("loop")).unwrap();
+
+        let files = find_files(dir.to_str().unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(files.unwrap(), vec![dir.join("2019-10.md")]);
+    }
+}
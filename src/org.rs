@@ -0,0 +1,466 @@
+use crate::parser::{self, tag, MDLogParser, TaskStyle};
+use crate::types::TaskState;
+use crate::writer;
+use chrono::naive::NaiveDate;
+
+use std::str::FromStr;
+
+/// The headline levels of week and day headings; items start one level below days
+const WEEK_LEVEL: usize = 1;
+const DAY_LEVEL: usize = 2;
+const ITEM_LEVEL: usize = 3;
+
+const BEGIN_SRC: &str = "#+BEGIN_SRC";
+const END_SRC: &str = "#+END_SRC";
+const CLOSED: &str = "CLOSED: [";
+const PROPERTIES: &str = ":PROPERTIES:";
+const END: &str = ":END:";
+/// The property holding priorities that are no Org priority cookie, e.g. ```A1```
+const PRIORITY: &str = ":MDLOG_PRIORITY:";
+
+/// Convert a log to an Org file:
+/// - week headings become level 1 headlines, day headings level 2 headlines
+/// - list items become headlines below their day, nested items one level deeper
+/// - task keywords and checkboxes become Org keywords, one letter priorities ```[#A]``` cookies
+///   and other priorities a ```:MDLOG_PRIORITY: A1``` property
+/// - completion dates in place of a priority become ```CLOSED: [2019-10-18 Fri]``` lines
+/// - events become active timestamps, e.g. ```<2019-10-14 Mon 16:25>```
+/// - everything else becomes body text and code blocks source blocks
+///
+/// The keywords of the parser are declared in a ```#+TODO:``` line.
+///
+/// # Example:
+/// ```
+/// use mdlog::org::to_org;
+/// use mdlog::parser::MDLogParser;
+///
+/// let log = "## Mon, 14.10.2019\n- EVT 16:25: b\n  some note\n- TODO A: c\n  - DONE: c1\n";
+/// let org = to_org(&MDLogParser::normalizing(), log);
+///
+/// assert_eq!(
+///     org.lines().skip(1).collect::<Vec<_>>(),
+///     vec![
+///         "** Mon, 14.10.2019",
+///         "*** <2019-10-14 Mon 16:25> b",
+///         "some note",
+///         "*** TODO [#A] c",
+///         "**** DONE c1",
+///     ]
+/// );
+/// ```
+pub fn to_org(parser: &MDLogParser, log_data: &str) -> String {
+    let tags = parser.tags();
+    let mut out = vec![todo_line(parser)];
+    let mut date = None;
+    let mut in_code = false;
+    // the widths of the indentation of the current item and its parents
+    let mut indents: Vec<usize> = vec![];
+
+    for (_, line) in parser::lines(log_data) {
+        let trimmed = line.trim_start();
+        if let Some(lang) = trimmed.strip_prefix(tag::CODE_FENCE) {
+            in_code = !in_code;
+            out.push(match (in_code, lang.trim()) {
+                (true, "") => BEGIN_SRC.to_string(),
+                (true, lang) => format!("{} {}", BEGIN_SRC, lang),
+                (false, _) => END_SRC.to_string(),
+            });
+            continue;
+        }
+        if in_code {
+            out.push(line.to_string());
+            continue;
+        }
+
+        let indent = line.len() - trimmed.len();
+        if let Some(heading) = line.strip_prefix(tag::WEEK) {
+            indents.clear();
+            out.push(format!("{} Week {}", stars(WEEK_LEVEL), heading));
+        } else if let Some(heading) = line.strip_prefix(tag::DAY) {
+            indents.clear();
+            date = parser.format().parse_day_heading(heading).ok();
+            out.push(format!("{} {}", stars(DAY_LEVEL), heading));
+        } else if let Some(item) = tags.strip_item(trimmed) {
+            while indents.last().map(|i| *i >= indent).unwrap_or(false) {
+                indents.pop();
+            }
+            indents.push(indent);
+            let level = ITEM_LEVEL + indents.len() - 1;
+            out.push(format!("{} {}", stars(level), headline(parser, item, date)));
+        } else {
+            if trimmed.is_empty() {
+                indents.clear();
+            }
+            out.push(trimmed.to_string());
+        }
+    }
+
+    let mut org = out.join(parser::LINE_END_LINUX);
+    org.push_str(parser::LINE_END_LINUX);
+    org
+}
+
+/// Convert an Org file written by [to_org] back to a log.  
+/// Level 1 and 2 headlines become week and day headings if they can be parsed as such,
+/// otherwise they are kept as text.
+pub fn from_org(parser: &MDLogParser, org: &str) -> String {
+    let mut out = vec![];
+    let mut in_code = false;
+    // the indentation of body text
+    let mut body_indent = String::new();
+    // the index, list marker and parts of the last task, which a CLOSED line belongs to
+    let mut last_task: Option<(usize, String, OrgTask)> = None;
+    let mut in_drawer = false;
+
+    for line in org.lines() {
+        if let Some(lang) = line.trim_start().strip_prefix(BEGIN_SRC) {
+            in_code = true;
+            out.push(format!("{}{}", tag::CODE_FENCE, lang.trim()));
+            continue;
+        }
+        if line.trim_start().starts_with(END_SRC) {
+            in_code = false;
+            out.push(tag::CODE_FENCE.to_string());
+            continue;
+        }
+        if in_code {
+            out.push(line.to_string());
+            continue;
+        }
+        // other settings have no counterpart in the log
+        if line.starts_with("#+") {
+            continue;
+        }
+
+        // drawers only hold the priority of the task above
+        if in_drawer || line.trim() == PROPERTIES {
+            in_drawer = line.trim() != END;
+            let priority = line.trim().strip_prefix(PRIORITY).map(str::trim);
+            if let (Some(p), Some((i, marker, task))) = (priority, &mut last_task) {
+                task.priority = Some(p.to_string());
+                out[*i] = task.line(parser, marker);
+            }
+            continue;
+        }
+        if let Some(date) = closed(line) {
+            if let Some((i, marker, task)) = &mut last_task {
+                task.closed = Some(date);
+                out[*i] = task.line(parser, marker);
+                continue;
+            }
+        }
+
+        let level = line.chars().take_while(|c| *c == '*').count();
+        let title = line[level..].strip_prefix(' ').map(str::trim);
+        let format = parser.format();
+        match (level, title) {
+            (WEEK_LEVEL, Some(title)) => {
+                body_indent.clear();
+                last_task = None;
+                let is_week = title
+                    .strip_prefix(tag::WEEK.trim_start_matches("# "))
+                    .map(|heading| format.parse_week_heading(heading).is_ok())
+                    .unwrap_or(false);
+                out.push(if is_week {
+                    format!("# {}", title)
+                } else {
+                    title.to_string()
+                });
+            }
+            (DAY_LEVEL, Some(title)) => {
+                body_indent.clear();
+                last_task = None;
+                out.push(match format.parse_day_heading(title) {
+                    Ok(_) => format!("{}{}", tag::DAY, title),
+                    Err(_) => title.to_string(),
+                });
+            }
+            (level, Some(title)) if level >= ITEM_LEVEL => {
+                let indent = tag::SUB.repeat(level - ITEM_LEVEL);
+                body_indent = format!("{}{}", indent, tag::SUB);
+                let marker = format!("{}{}", indent, tag::ITEM);
+                last_task = None;
+                match OrgTask::parse(parser, title) {
+                    Some(task) => {
+                        out.push(task.line(parser, &marker));
+                        last_task = Some((out.len() - 1, marker, task));
+                    }
+                    None => out.push(format!("{}{}", marker, item(parser, title))),
+                }
+            }
+            _ if line.trim().is_empty() => {
+                body_indent.clear();
+                last_task = None;
+                out.push(String::new());
+            }
+            _ => {
+                last_task = None;
+                out.push(format!("{}{}", body_indent, line.trim()));
+            }
+        }
+    }
+
+    let mut log = out.join(parser::LINE_END_LINUX);
+    log.push_str(parser::LINE_END_LINUX);
+    log
+}
+
+fn stars(level: usize) -> String {
+    "*".repeat(level)
+}
+
+/// The ```#+TODO:``` line declaring the keywords of all states
+fn todo_line(parser: &MDLogParser) -> String {
+    use self::TaskState::*;
+
+    let keywords = |states: &[TaskState]| {
+        states
+            .iter()
+            .filter_map(|s| parser.tags().tag_for(*s, TaskStyle::Keyword))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    format!(
+        "#+TODO: {} | {}",
+        keywords(&[Todo, Doing, Waiting]),
+        keywords(&[Done, Cancelled])
+    )
+}
+
+/// The headline of a top-level or nested item
+fn headline(parser: &MDLogParser, item: &str, date: Option<NaiveDate>) -> String {
+    let tags = parser.tags();
+    if let Some(t) = tags.task_tag(item) {
+        if let Some(keyword) = tags.tag_for(t.state, TaskStyle::Keyword) {
+            let priority = t.priority(item);
+            let closed = priority.and_then(|p| NaiveDate::from_str(p).ok());
+            return match (priority, closed) {
+                (_, Some(d)) => format!(
+                    "{} {}{}{}{}]",
+                    keyword,
+                    t.msg(item),
+                    parser::LINE_END_LINUX,
+                    CLOSED,
+                    d.format("%Y-%m-%d %a")
+                ),
+                // Org priority cookies are a single letter
+                (Some(p), None) if p.len() == 1 => {
+                    format!("{} [#{}] {}", keyword, p, t.msg(item))
+                }
+                (Some(p), None) => [
+                    format!("{} {}", keyword, t.msg(item)),
+                    PROPERTIES.to_string(),
+                    format!("{} {}", PRIORITY, p),
+                    END.to_string(),
+                ]
+                .join(parser::LINE_END_LINUX),
+                (None, None) => format!("{} {}", keyword, t.msg(item)),
+            };
+        }
+    }
+
+    let event = tags
+        .event(item)
+        .and_then(|kw| parser::event_msg(&item[kw.len()..]));
+    match (event, date) {
+        (Some((msg, Some(time), end)), Some(date)) => {
+            let ts = date.format("%Y-%m-%d %a");
+            format!("<{} {}> {}", ts, parser::event_time(time, end), msg)
+        }
+        (Some((msg, None, _)), Some(date)) => format!("<{}> {}", date.format("%Y-%m-%d %a"), msg),
+        _ => item.to_string(),
+    }
+}
+
+/// The parts of a task headline
+struct OrgTask {
+    state: TaskState,
+    priority: Option<String>,
+    msg: String,
+    /// the date of the ```CLOSED``` line below the headline
+    closed: Option<NaiveDate>,
+}
+
+impl OrgTask {
+    /// The task of a headline starting with one of the keywords of the parser
+    fn parse(parser: &MDLogParser, title: &str) -> Option<Self> {
+        let (first, rest) = title.split_once(' ').unwrap_or((title, ""));
+        let state = parser
+            .tags()
+            .tasks
+            .iter()
+            .find(|(kw, _)| kw == first)
+            .map(|(_, state)| *state)?;
+
+        let rest = rest.trim_start();
+        let (priority, msg) = match rest.strip_prefix("[#").and_then(|r| r.split_once(']')) {
+            Some((p, msg)) => (Some(p.to_string()), msg.trim_start()),
+            None => (None, rest),
+        };
+        Some(OrgTask {
+            state,
+            priority,
+            msg: msg.to_string(),
+            closed: None,
+        })
+    }
+
+    /// The list item of the task, written as [writer::task_lines] writes keyword tasks
+    fn line(&self, parser: &MDLogParser, marker: &str) -> String {
+        writer::item_line(
+            marker,
+            &self.msg,
+            self.state,
+            self.priority.as_deref(),
+            self.closed,
+            parser.tags(),
+            TaskStyle::Keyword,
+        )
+    }
+}
+
+/// The item text of a headline that is not a task
+fn item(parser: &MDLogParser, title: &str) -> String {
+    let tags = parser.tags();
+    let event = tags.events.first().map(String::as_str).unwrap_or(tag::EVT);
+    match timestamp(title) {
+        Some((Some(times), msg)) => format!("{} {}: {}", event, times, msg),
+        Some((None, msg)) => format!("{}: {}", event, msg),
+        None => title.to_string(),
+    }
+}
+
+/// The date of a ```CLOSED: [2019-10-18 Fri]``` line
+fn closed(line: &str) -> Option<NaiveDate> {
+    let rest = line.trim().strip_prefix(CLOSED)?;
+    let date = rest.split([' ', ']']).next()?;
+    NaiveDate::from_str(date).ok()
+}
+
+/// The times (if any) of the active timestamp at the start of the title, as written in events,
+/// and the rest of the title
+fn timestamp(title: &str) -> Option<(Option<String>, &str)> {
+    let (ts, rest) = title.strip_prefix('<')?.split_once('>')?;
+    let mut parts = ts.split_whitespace();
+    NaiveDate::from_str(parts.next()?).ok()?;
+    let _weekday = parts.next();
+    let times = match parts.next() {
+        Some(t) => {
+            let (_, time, end) = parser::event_msg(t)?;
+            Some(parser::event_time(time?, end))
+        }
+        None => None,
+    };
+    Some((times, rest.trim_start()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::Tags;
+
+    const EXAMPLE_DATA: &str = "# Week 42, 14.10.2019 - 20.10.2019
+
+## Mon, 14.10.2019
+- a
+- EVT 16:25: b
+  - b1
+    b2
+- EVT 17:00-18:30: b3
+- TODO A1: c
+  - DONE: c1
+    - WAIT: c11
+
+## Tue, 15.10.2019
+- EVT: e
+- CANCELLED: f
+- DONE 2019-10-18: g
+```rust
+# not a headline
+```
+";
+
+    #[test]
+    fn round_trip() {
+        let p = MDLogParser::builder()
+            .normalize(true)
+            .tags(Tags::extended())
+            .build();
+        let org = to_org(&p, EXAMPLE_DATA);
+
+        assert_eq!(
+            org,
+            "#+TODO: TODO DOING WAIT | DONE CANCELLED
+* Week 42, 14.10.2019 - 20.10.2019
+
+** Mon, 14.10.2019
+*** a
+*** <2019-10-14 Mon 16:25> b
+**** b1
+b2
+*** <2019-10-14 Mon 17:00-18:30> b3
+*** TODO c
+:PROPERTIES:
+:MDLOG_PRIORITY: A1
+:END:
+**** DONE c1
+***** WAIT c11
+
+** Tue, 15.10.2019
+*** <2019-10-15 Tue> e
+*** CANCELLED f
+*** DONE g
+CLOSED: [2019-10-18 Fri]
+#+BEGIN_SRC rust
+# not a headline
+#+END_SRC
+"
+        );
+
+        assert_eq!(from_org(&p, &org), EXAMPLE_DATA);
+    }
+
+    #[test]
+    fn closed_with_priority() {
+        let p = MDLogParser::normalizing();
+        let org = "** Fri, 18.10.2019\n*** DONE [#B] b\nCLOSED: [2019-10-18 Fri]\n";
+        let log = from_org(&p, org);
+        assert_eq!(log, "## Fri, 18.10.2019\n- DONE B: b done:2019-10-18\n");
+
+        let tasks = p.parse_tasks(&log).unwrap();
+        assert_eq!(tasks[0].priority.as_deref(), Some("B"));
+        assert_eq!(tasks[0].completed, Some(NaiveDate::from_ymd(2019, 10, 18)));
+    }
+
+    #[test]
+    fn headlines() {
+        let p = MDLogParser::normalizing();
+        let org = [
+            "* Notes",
+            "* Week 42, 14.10.2019 - 20.10.2019",
+            "** Mon, 14.10.2019",
+            "*** TODO [#B] a",
+            "** Open questions",
+        ]
+        .join("\n");
+        assert_eq!(
+            from_org(&p, &org),
+            [
+                "Notes",
+                "# Week 42, 14.10.2019 - 20.10.2019",
+                "## Mon, 14.10.2019",
+                "- TODO B: a",
+                "Open questions",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn checkboxes() {
+        let p = MDLogParser::normalizing();
+        let org = to_org(&p, "## Mon, 14.10.2019\n- [ ] a\n  - [x] a1\n");
+        assert!(org.ends_with("*** TODO a\n**** DONE a1\n"));
+    }
+}
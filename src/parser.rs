@@ -1,22 +1,39 @@
-use crate::types::{Birthday, Event, Person, Subtask, Task};
+use crate::format::LogFormat;
+use crate::types::{
+    marker_date, Birthday, Entry, Event, Occasion, OccasionKind, Person, Span, Subtask, Task,
+    TaskState, Week,
+};
 use chrono::naive::{NaiveDate, NaiveTime};
+use serde::Deserialize;
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{self, ErrorKind, Read};
-use std::mem;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read};
+use std::ops::Range;
 use std::path::Path;
 use std::str::FromStr;
-use std::usize;
 
 pub mod tag {
     pub const ITEM: &str = "- ";
+    pub const ITEM_STAR: &str = "* ";
     pub const DAY: &str = "## ";
     pub const WEEK: &str = "# Week ";
     pub const TOPLEVEL: &str = "";
+    pub const CODE_FENCE: &str = "```";
     pub const SUB: &str = "  ";
     pub const TODO: &str = "TODO";
+    pub const DOING: &str = "DOING";
+    pub const WAIT: &str = "WAIT";
     pub const DONE: &str = "DONE";
+    pub const CANCELLED: &str = "CANCELLED";
+    /// Marks the completion date in the message of a task, e.g. ```done:2019-10-18```
+    pub const DONE_MARKER: &str = "done:";
+    /// Marks the identity of a task copied over several days, e.g. ```id:report```
+    pub const ID_MARKER: &str = "id:";
+    pub const CHECKBOX_OPEN: &str = "[ ]";
+    pub const CHECKBOX_CHECKED: &str = "[x]";
+    pub const CHECKBOX_CHECKED_UPPER: &str = "[X]";
     pub const EVT: &str = "EVT";
     // event without
     pub const EVT_PLAIN: &str = "EVT: ";
@@ -25,104 +42,373 @@ pub mod tag {
 pub const LINE_END_LINUX: &str = "\n";
 pub const LINE_END_WINDOWS: &str = "\r\n";
 
-/// Parser for tagged markdown files.  
-/// Supports both linux and windows line endings.
-pub struct MDLogParser {
+/// Guess the line end used in the given data by picking the more frequent one.  
+/// Defaults to linux line ends if there are none.
+pub fn detect_line_end(s: &str) -> &'static str {
+    let crlf = s.matches(LINE_END_WINDOWS).count();
+    let lf = s.matches(LINE_END_LINUX).count() - crlf;
+    if crlf > lf {
+        LINE_END_WINDOWS
+    } else {
+        LINE_END_LINUX
+    }
+}
+
+/// The tag vocabulary understood by the parser
+#[derive(Clone, Debug)]
+pub struct Tags {
+    /// Markers starting a list item, e.g. ```- ```
+    pub items: Vec<String>,
+    /// Keywords marking a list item as task and the state they stand for
+    pub tasks: Vec<(String, TaskState)>,
+    /// Checkboxes marking a list item as task and the state they stand for, e.g. ```[x]```
+    pub checkboxes: Vec<(String, TaskState)>,
+    /// Keywords marking a list item as event
+    pub events: Vec<String>,
+}
+
+/// ```- ``` items, ```TODO```, ```DONE``` and ```EVT``` keywords and checkboxes
+impl Default for Tags {
+    fn default() -> Self {
+        Self {
+            items: vec![tag::ITEM.into()],
+            tasks: vec![
+                (tag::TODO.into(), TaskState::Todo),
+                (tag::DONE.into(), TaskState::Done),
+            ],
+            checkboxes: vec![
+                (tag::CHECKBOX_OPEN.into(), TaskState::Todo),
+                (tag::CHECKBOX_CHECKED.into(), TaskState::Done),
+                (tag::CHECKBOX_CHECKED_UPPER.into(), TaskState::Done),
+            ],
+            events: vec![tag::EVT.into()],
+        }
+    }
+}
+
+impl Tags {
+    /// The default tags along with ```* ``` items and ```DOING```, ```WAIT``` and ```CANCELLED```
+    /// keywords, e.g. for [MDLogParserBuilder::tags]
+    pub fn extended() -> Self {
+        let mut tags = Tags::default();
+        tags.items.push(tag::ITEM_STAR.into());
+        tags.tasks.extend_from_slice(&[
+            (tag::DOING.into(), TaskState::Doing),
+            (tag::WAIT.into(), TaskState::Waiting),
+            (tag::CANCELLED.into(), TaskState::Cancelled),
+        ]);
+        tags
+    }
+
+    /// Strip the item marker from the front of the (unindented) line
+    pub fn strip_item<'a>(&self, line: &'a str) -> Option<&'a str> {
+        self.items
+            .iter()
+            .find(|i| line.starts_with(i.as_str()))
+            .map(|i| &line[i.len()..])
+    }
+
+    /// The task keyword or checkbox the given item text starts with.  
+    /// Picks the longest keyword if several match.
+    pub fn task_tag<'a>(&'a self, item: &str) -> Option<TaskTag<'a>> {
+        let keywords = self
+            .tasks
+            .iter()
+            .map(|(kw, state)| (kw, state, TaskStyle::Keyword));
+        let checkboxes = self
+            .checkboxes
+            .iter()
+            .map(|(kw, state)| (kw, state, TaskStyle::Checkbox));
+
+        keywords
+            .chain(checkboxes)
+            .filter(|(kw, _, _)| starts_with_keyword(item, kw))
+            .max_by_key(|(kw, _, _)| kw.len())
+            .map(|(kw, state, style)| TaskTag {
+                keyword: kw.as_str(),
+                state: *state,
+                style,
+            })
+    }
+
+    /// The first tag of the given style standing for the given state
+    pub fn tag_for(&self, state: TaskState, style: TaskStyle) -> Option<&str> {
+        let tags = match style {
+            TaskStyle::Keyword => &self.tasks,
+            TaskStyle::Checkbox => &self.checkboxes,
+        };
+        tags.iter()
+            .find(|(_, s)| *s == state)
+            .map(|(kw, _)| kw.as_str())
+    }
+
+    /// The event keyword the given item text starts with
+    pub fn event<'a>(&'a self, item: &str) -> Option<&'a str> {
+        self.events
+            .iter()
+            .filter(|kw| starts_with_keyword(item, kw))
+            .max_by_key(|kw| kw.len())
+            .map(|kw| kw.as_str())
+    }
+
+    /// Whether the given item text has task keywords for different states in front of its message.
+    /// Keywords are only matched as whole words, so ```DONE: finish TODO list``` is fine.
+    pub fn is_conflicting(&self, item: &str) -> bool {
+        let head = item.split(':').next().unwrap_or(item);
+        let words: Vec<_> = head.split_whitespace().collect();
+        let mut states = self
+            .tasks
+            .iter()
+            .filter(|(kw, _)| words.contains(&kw.as_str()))
+            .map(|(_, state)| state);
+        match states.next() {
+            Some(first) => states.any(|s| s != first),
+            None => false,
+        }
+    }
+}
+
+/// The ways a list item can be marked as task
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum TaskStyle {
+    /// e.g. ```- TODO: thing```
+    Keyword,
+    /// e.g. ```- [ ] thing```
+    Checkbox,
+}
+
+impl FromStr for TaskStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keyword" => Ok(Self::Keyword),
+            "checkbox" => Ok(Self::Checkbox),
+            _ => Err(format!(
+                "Unknown task style '{}'; use keyword or checkbox",
+                s
+            )),
+        }
+    }
+}
+
+/// The tag marking a list item as task
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct TaskTag<'a> {
+    pub keyword: &'a str,
+    pub state: TaskState,
+    pub style: TaskStyle,
+}
+
+impl<'a> TaskTag<'a> {
+    /// The message of the task given the item text starting with this tag
+    pub fn msg<'b>(&self, item: &'b str) -> &'b str {
+        let rest = &item[self.keyword.len()..];
+        match self.style {
+            TaskStyle::Keyword => task_msg(rest).1,
+            TaskStyle::Checkbox => rest.trim(),
+        }
+    }
+
+    /// The priority of the task given the item text starting with this tag, e.g. ```A1``` in ```TODO A1: x```.
+    /// A completion date in its place (e.g. ```DONE 2019-10-18: x```) is returned as well.
+    pub fn priority<'b>(&self, item: &'b str) -> Option<&'b str> {
+        match self.style {
+            TaskStyle::Keyword => task_msg(&item[self.keyword.len()..]).0,
+            TaskStyle::Checkbox => None,
+        }
+    }
+}
+
+/// Builder for configuring a [MDLogParser]
+pub struct MDLogParserBuilder {
     line_end: String,
-    unit_ends: [String; 4],
-    task_tag_todo: String,
-    task_tag_done: String,
-    event_tag: String,
-    day_tag: String,
+    normalize: bool,
+    format: LogFormat,
+    tags: Tags,
 }
 
-impl MDLogParser {
-    /// Create a new parser based on the given line end.
-    pub fn from_line_end(line_end: &str) -> Self {
-        let le = line_end.to_owned();
-        let task_tag_todo = le.clone() + tag::ITEM + tag::TODO;
-        let task_tag_done = le.clone() + tag::ITEM + tag::DONE;
-        let event_tag = le.clone() + tag::ITEM + tag::EVT;
+impl Default for MDLogParserBuilder {
+    fn default() -> Self {
+        Self {
+            line_end: LINE_END_LINUX.into(),
+            normalize: false,
+            format: LogFormat::default(),
+            tags: Tags::default(),
+        }
+    }
+}
+
+impl MDLogParserBuilder {
+    pub fn line_end(self, line_end: &str) -> Self {
+        Self {
+            line_end: line_end.into(),
+            ..self
+        }
+    }
+
+    /// Convert all windows line ends to linux ones before parsing
+    pub fn normalize(self, normalize: bool) -> Self {
+        Self { normalize, ..self }
+    }
+
+    /// The format used for parsing day headings
+    pub fn format(self, format: LogFormat) -> Self {
+        Self { format, ..self }
+    }
+
+    /// Replace the whole tag vocabulary
+    pub fn tags(self, tags: Tags) -> Self {
+        Self { tags, ..self }
+    }
+
+    /// Add a list item marker, e.g. ```+ ```
+    pub fn item(mut self, marker: &str) -> Self {
+        self.tags.items.push(marker.into());
+        self
+    }
+
+    /// Add a task keyword standing for the given state, e.g. ```LATER```
+    pub fn task(mut self, keyword: &str, state: TaskState) -> Self {
+        self.tags.tasks.push((keyword.into(), state));
+        self
+    }
+
+    /// Add a checkbox standing for the given state, e.g. ```[-]```
+    pub fn checkbox(mut self, checkbox: &str, state: TaskState) -> Self {
+        self.tags.checkboxes.push((checkbox.into(), state));
+        self
+    }
+
+    /// Add an event keyword, e.g. ```MTG```
+    pub fn event(mut self, keyword: &str) -> Self {
+        self.tags.events.push(keyword.into());
+        self
+    }
+
+    pub fn build(self) -> MDLogParser {
+        let le = self.line_end;
         let day_tag = le.clone() + tag::DAY;
 
-        let unit_ends = [
+        let mut unit_ends: Vec<String> = self
+            .tags
+            .items
+            .iter()
             // terminated with the next top-level list item
-            le.clone() + tag::TOPLEVEL + tag::ITEM,
+            .map(|i| le.clone() + tag::TOPLEVEL + i)
+            .collect();
+        unit_ends.extend_from_slice(&[
             // terminated with an empty line
-            // FIXME: this will break if there is now trailing newline
-            le.clone() + line_end,
+            // (or the end of the input, see lookup_end_of_unit)
+            le.clone() + &le,
             // terminated  by the next day
             // FIXME: might go wrong if there is a codeblock in between which contains ##
             day_tag.clone(),
             // terminated at the begin of a week
             le.clone() + tag::WEEK,
-        ];
+        ]);
 
-        Self {
+        MDLogParser {
             line_end: le,
+            normalize: self.normalize,
             unit_ends,
-            task_tag_done,
-            task_tag_todo,
-            event_tag,
             day_tag,
+            format: self.format,
+            tags: self.tags,
         }
     }
+}
+
+/// Parser for tagged markdown files.  
+/// Supports both linux and windows line endings.
+#[derive(Debug)]
+pub struct MDLogParser {
+    line_end: String,
+    /// convert all windows line ends to linux ones before parsing
+    normalize: bool,
+    unit_ends: Vec<String>,
+    day_tag: String,
+    format: LogFormat,
+    tags: Tags,
+}
 
-    /// Parse events
+impl MDLogParser {
+    /// Configure a new parser
+    pub fn builder() -> MDLogParserBuilder {
+        MDLogParserBuilder::default()
+    }
+
+    /// Create a new parser based on the given line end.
+    pub fn from_line_end(line_end: &str) -> Self {
+        Self::builder().line_end(line_end).build()
+    }
+
+    /// Use the given format for parsing day headings
+    pub fn with_format(self, format: LogFormat) -> Self {
+        Self { format, ..self }
+    }
+
+    /// Create a new parser for the line end used in the given data.
+    pub fn detect(log_data: &str) -> Self {
+        Self::from_line_end(detect_line_end(log_data))
+    }
+
+    /// Create a new parser which normalises all line ends before parsing.  
+    /// Use this for files with mixed line ends.
+    pub fn normalizing() -> Self {
+        Self::builder().normalize(true).build()
+    }
+
+    /// The tag vocabulary of this parser
+    pub fn tags(&self) -> &Tags {
+        &self.tags
+    }
+
+    /// The format of the headings of this parser
+    pub fn format(&self) -> &LogFormat {
+        &self.format
+    }
+
+    pub(crate) fn line_end(&self) -> &str {
+        &self.line_end
+    }
+
+    /// What ends a unit, each starting with the line end, see [MDLogParser::lookup_end_of_unit]
+    pub(crate) fn unit_ends(&self) -> &[String] {
+        &self.unit_ends
+    }
+
+    /// Parse events.  
+    /// Events with a malformed time are kept without a time, with all of it as message;
+    /// reporting them is left to [crate::lint].
     pub fn parse_events(&self, log_data: &str) -> io::Result<Vec<Event>> {
+        let prepared = self.prepare(log_data);
+        let log_data = &*prepared.data;
         let mut events = vec![];
-        for (start, _) in log_data.match_indices(&self.event_tag) {
-            // isolate line and skip the leading CRLF
-            let start = start + self.line_end.len();
-            let (_eol, line) = slice(log_data, start, &self.line_end);
-
-            let date = self.lookup_date(log_data, start)?;
-
-            // parse time (if any)
-            let (msg, time) = match line.find("EVT:") {
-                // straightforward event (e.g. - EVT:) (skip the first 6 chars)
-                Some(_pos) => (line["- EVT:".len()..].trim_start().to_string(), None),
-                // event with time ( e.g.- EVT 16:49:)
-                // skip to the first number
-                None => {
-                    let mut hm = line["- EVT ".len()..]
-                        .split(|c: char| c.eq(&':'))
-                        .map(|n| u32::from_str(n).unwrap());
-                    let h = hm.next().unwrap();
-                    let m = hm.next().unwrap();
-
-                    let time = NaiveTime::from_hms(h, m, 0);
-
-                    let msg = line
-                        .match_indices(':')
-                        .skip(1)
-                        .map(|(pos, _)| &line[pos + ":".len()..])
-                        .map(|msg| msg.trim_start().to_string())
-                        .next()
-                        .unwrap();
-
-                    (msg, Some(time))
-                }
+        for (start, item) in self.find_items(log_data) {
+            let keyword = match self.tags.event(item) {
+                Some(kw) => kw,
+                None => continue,
             };
+            let (eol, _) = slice(log_data, start, &self.line_end);
+
+            let date = self.lookup_date(&prepared, start)?;
 
-            let start_of_unit = start + self.line_end.len();
+            let rest = &item[keyword.len()..];
+            let (msg, time, end) = event_msg(rest).unwrap_or((rest.trim(), None, None));
+            let msg = msg.to_string();
 
-            let end_of_unit = start_of_unit + self.lookup_end_of_unit(&log_data[start_of_unit..]);
+            let end_of_unit = self.lookup_end_of_unit(&prepared, eol);
 
-            let notes = log_data[start_of_unit..end_of_unit]
-                .lines()
-                .skip(1)
-                .filter(|l| !l.is_empty())
-                .map(|l| l.trim_start())
-                .map(|l| &l[2..])
-                .map(|l| l.to_string())
-                .collect();
+            let notes = self.notes(&log_data[eol..end_of_unit]);
 
             let event = Event {
                 msg,
                 notes,
                 date,
                 time,
+                end,
+                span: prepared.span(start..end_of_unit),
             };
 
             events.push(event);
@@ -131,87 +417,112 @@ impl MDLogParser {
         Ok(events)
     }
 
+    /// Parse all top-level items which are neither tasks nor events
+    pub fn parse_entries(&self, log_data: &str) -> io::Result<Vec<Entry>> {
+        let prepared = self.prepare(log_data);
+        let log_data = &*prepared.data;
+        let mut entries = vec![];
+        for (start, item) in self.find_items(log_data) {
+            if self.tags.task_tag(item).is_some() || self.tags.event(item).is_some() {
+                continue;
+            }
+            let (eol, _) = slice(log_data, start, &self.line_end);
+            let date = self.lookup_date(&prepared, start)?;
+            let end_of_unit = self.lookup_end_of_unit(&prepared, eol);
+
+            entries.push(Entry {
+                msg: item.trim().to_string(),
+                notes: self.notes(&log_data[eol..end_of_unit]),
+                date,
+                span: prepared.span(start..end_of_unit),
+            });
+        }
+
+        Ok(entries)
+    }
+
     pub fn parse_tasks(&self, log_data: &str) -> io::Result<Vec<Task>> {
-        // find toplevel TODOS
+        let prepared = self.prepare(log_data);
+        let log_data = &*prepared.data;
+        // find toplevel tasks
         let mut tasks = vec![];
 
-        let todos = log_data
-            .match_indices(&self.task_tag_todo)
-            .map(|(idx, _)| (idx, false));
-        let dones = log_data
-            .match_indices(&self.task_tag_done)
-            .map(|(idx, _)| (idx, true));
-
-        for (idx, is_done) in todos.chain(dones) {
-            let (todo_start, todo_line, eol) = {
-                let ip = idx + self.line_end.len();
-                let eol = log_data[ip..].find(&self.line_end).unwrap();
-                (ip, &log_data[ip..ip + eol], eol)
+        for (todo_start, item) in self.find_items(log_data) {
+            let task_tag = match self.tags.task_tag(item) {
+                Some(t) => t,
+                None => continue,
             };
+            let (eol, _todo_line) = slice(log_data, todo_start, &self.line_end);
 
             // search backwards from the TODO to find the day
-            let date = self.lookup_date(log_data, todo_start)?;
+            let date = self.lookup_date(&prepared, todo_start)?;
 
             // search forward from the task
             // to identify the end of the task
-            let end_of_todo = self.lookup_end_of_unit(&log_data[todo_start..]);
+            let end_of_todo = self.lookup_end_of_unit(&prepared, eol);
 
-            let todo_body = &log_data[todo_start + eol..todo_start + end_of_todo];
+            let todo_body = &log_data[eol..end_of_todo];
 
             let (subtasks, notes) =
-                todo_body
-                    .lines()
-                    .fold((vec![], vec![]), |(mut st, mut n), l| {
-                        let l = {
-                            let pos = l
-                                .find(tag::ITEM)
-                                .map(|pos| pos + tag::ITEM.len())
-                                .unwrap_or(0);
-                            &l[pos..]
-                        };
-
-                        if l.is_empty() {
-                            return (st, n);
+                lines(todo_body).fold((vec![], vec![]), |(mut st, mut n), (offset, l)| {
+                    let line_start = eol + offset;
+                    let line_end = line_start + l.len();
+                    let l = l.trim_start();
+                    let column_start = line_end - l.len();
+                    let l = self.tags.strip_item(l).unwrap_or(l);
+
+                    if l.is_empty() {
+                        return (st, n);
+                    }
+
+                    // subtasks with keywords for several states take the state of the first one;
+                    // reported by the linter
+                    match self.tags.task_tag(l) {
+                        Some(t) => {
+                            let s = Subtask {
+                                msg: t.msg(l).into(),
+                                state: t.state,
+                                span: prepared.span(column_start..line_end),
+                            };
+                            st.push(s);
                         }
+                        None => n.push(l.to_string()),
+                    };
 
-                        match (l.find(tag::TODO), l.find(tag::DONE)) {
-                            (Some(_todo), Some(_done)) => eprintln!(
-                                "Found TODO and DONE in {}. A task can either be done or todo.",
-                                l
-                            ),
-                            (Some(_todo), None) => {
-                                let s = Subtask {
-                                    msg: slice_from(&l, ": ").into(),
-                                    is_done: false,
-                                };
-                                st.push(s);
-                            }
-                            (None, Some(_done)) => {
-                                let s = Subtask {
-                                    msg: slice_from(&l, ": ").into(),
-                                    is_done: true,
-                                };
-                                st.push(s);
-                            }
-                            (None, None) => n.push(l.to_string()),
-                        };
-
-                        (st, n)
-                    });
+                    (st, n)
+                });
 
             // drop the TODO at the front
-            let msg = slice_from(todo_line, ": ").to_owned();
+            let msg = task_tag.msg(item).to_owned();
 
-            // check if there are any undone subtasks
-            let all_subtasks_done = !subtasks.iter().any(|st| !st.is_done);
-            let is_done = is_done && all_subtasks_done;
+            // a task is only done if all subtasks are closed as well
+            let all_subtasks_closed = subtasks.iter().all(|st| st.state.is_closed());
+            let state = match task_tag.state {
+                TaskState::Done if !all_subtasks_closed => TaskState::Todo,
+                state => state,
+            };
+
+            // a date in place of the priority is the completion date
+            let (priority, completed) = match task_tag.priority(item) {
+                Some(p) => match NaiveDate::parse_from_str(p, "%Y-%m-%d") {
+                    Ok(d) => (None, Some(d)),
+                    Err(_) => (Some(p.to_string()), None),
+                },
+                None => (None, None),
+            };
+            let completed = completed
+                .or_else(|| marker_date(&msg, tag::DONE_MARKER))
+                .filter(|_| state.is_closed());
 
             let task = Task {
                 msg,
                 subtasks,
                 notes,
                 date,
-                is_done,
+                state,
+                priority,
+                completed,
+                span: prepared.span(todo_start..end_of_todo),
             };
 
             tasks.push(task);
@@ -220,47 +531,237 @@ impl MDLogParser {
         Ok(tasks)
     }
 
-    fn lookup_date(&self, s: &str, lookup_from: usize) -> io::Result<NaiveDate> {
-        let day_line = {
-            let day = s[..lookup_from].rfind(&self.day_tag).unwrap() + 1;
-            let eol = s[day..].find(&self.line_end).unwrap();
-            &s[day..day + eol]
-        };
+    /// Parse week headings along with the day headings belonging to each week.  
+    /// Day headings in front of the first week heading are not part of any week.
+    pub fn parse_weeks(&self, log_data: &str) -> io::Result<Vec<Week>> {
+        let prepared = self.prepare(log_data);
+        let log_data = &*prepared.data;
+
+        let mut weeks = vec![];
+        let mut current: Option<(usize, Week)> = None;
+
+        for (start, line) in self.find_headings(log_data) {
+            if let Some(heading) = line.strip_prefix(tag::WEEK) {
+                let (number, week_start, week_end) = self.format.parse_week_heading(heading)?;
+                if let Some((week_pos, mut week)) = current.take() {
+                    week.span = prepared.span(week_pos..start);
+                    weeks.push(week);
+                }
+                let week = Week {
+                    number,
+                    start: week_start,
+                    end: week_end,
+                    days: vec![],
+                    span: Span::default(),
+                };
+                current = Some((start, week));
+            } else if let Some(heading) = line.strip_prefix(tag::DAY) {
+                if let Some((_, week)) = current.as_mut() {
+                    week.days.push(self.format.parse_day_heading(heading)?);
+                }
+            }
+        }
 
-        // strip out all shit including control characters and delimiters
-        // since we always use dd.mm.yyyy
-        let dmy: String = day_line.chars().filter(|c| char::is_numeric(*c)).collect();
+        if let Some((week_pos, mut week)) = current.take() {
+            week.span = prepared.span(week_pos..log_data.len());
+            weeks.push(week);
+        }
 
-        NaiveDate::parse_from_str(&dmy, "%d%m%Y").map_err(|e| {
-            io::Error::new(
-                ErrorKind::InvalidInput,
-                format!("Parsing '{}' failed with {}", day_line, e),
-            )
-        })
+        Ok(weeks)
     }
 
-    /// A unit is a number of lines with higher level of indentation than the preceding line
-    fn lookup_end_of_unit(&self, s: &str) -> usize {
-        self.unit_ends
+    /// Find all week and day headings.  
+    /// Returns the position of each heading line and the line.
+    fn find_headings<'a>(&self, log_data: &'a str) -> Vec<(usize, &'a str)> {
+        let mut headings: Vec<(usize, &'a str)> = [tag::WEEK, tag::DAY]
             .iter()
-            .filter_map(|unit_end| s.find(unit_end))
-            .min()
-            .unwrap_or_else(|| panic!("Failed to find unit delimiter in: {}", s))
+            .flat_map(|h| {
+                let pattern = self.line_end.clone() + h;
+                let at_start = if log_data.starts_with(h) {
+                    Some(0)
+                } else {
+                    None
+                };
+                at_start
+                    .into_iter()
+                    .chain(
+                        log_data
+                            .match_indices(&pattern)
+                            .map(|(idx, _)| idx + self.line_end.len()),
+                    )
+                    .collect::<Vec<_>>()
+            })
+            .map(|start| (start, slice(log_data, start, &self.line_end).1))
+            .collect();
+        headings.sort_by_key(|(start, _)| *start);
+        headings
+    }
+
+    /// Find all top-level list items.  
+    /// Returns the position of each item line and its text following the item marker.
+    fn find_items<'a>(&self, log_data: &'a str) -> Vec<(usize, &'a str)> {
+        let mut items: Vec<(usize, &'a str)> = self
+            .tags
+            .items
+            .iter()
+            .flat_map(|i| {
+                let pattern = self.line_end.clone() + tag::TOPLEVEL + i;
+                log_data
+                    .match_indices(&pattern)
+                    .map(|(idx, _)| idx + self.line_end.len())
+                    .map(move |start| (start, i.len()))
+                    .collect::<Vec<_>>()
+            })
+            .map(|(start, marker_len)| {
+                let (eol, _) = slice(log_data, start, &self.line_end);
+                (start, &log_data[start + marker_len..eol])
+            })
+            .collect();
+        items.sort_by_key(|(start, _)| *start);
+        items
+    }
+
+    /// The notes in the body of an event or entry, one per non-empty line
+    fn notes(&self, body: &str) -> Vec<String> {
+        body.lines()
+            .map(|l| l.trim_start())
+            .filter(|l| !l.is_empty())
+            .map(|l| self.tags.strip_item(l).unwrap_or(l))
+            .map(|l| l.to_string())
+            .collect()
+    }
+
+    /// Normalise the line ends of the given data if the parser is set to do so
+    fn prepare<'a>(&self, log_data: &'a str) -> Prepared<'a> {
+        let mut dropped = vec![];
+        let data = if self.normalize && log_data.contains(LINE_END_WINDOWS) {
+            let mut data = String::with_capacity(log_data.len());
+            let mut last = 0;
+            for (pos, _) in log_data.match_indices(LINE_END_WINDOWS) {
+                data.push_str(&log_data[last..pos]);
+                dropped.push(data.len());
+                last = pos + "\r".len();
+            }
+            data.push_str(&log_data[last..]);
+            Cow::Owned(data)
+        } else {
+            Cow::Borrowed(log_data)
+        };
+
+        let line_starts = std::iter::once(0)
+            .chain(data.match_indices(LINE_END_LINUX).map(|(pos, _)| pos + 1))
+            .collect();
+
+        // all unit ends and day tags start with a line end, so looking at every line end
+        // once finds them all, including overlapping ones
+        let mut unit_ends = vec![];
+        let mut day_tags = vec![];
+        for (pos, _) in data.match_indices(self.line_end.as_str()) {
+            let rest = &data[pos..];
+            if self.unit_ends.iter().any(|e| rest.starts_with(e.as_str())) {
+                unit_ends.push(pos);
+            }
+            if rest.starts_with(&self.day_tag) {
+                day_tags.push(pos);
+            }
+        }
+
+        Prepared {
+            data,
+            dropped,
+            line_starts,
+            unit_ends,
+            day_tags,
+        }
+    }
+
+    /// The date of the last day heading in front of the given position
+    fn lookup_date(&self, prepared: &Prepared, lookup_from: usize) -> io::Result<NaiveDate> {
+        let s = &*prepared.data;
+        let day_line = {
+            let before = prepared
+                .day_tags
+                .partition_point(|pos| pos + self.day_tag.len() <= lookup_from);
+            let day = match before.checked_sub(1) {
+                Some(i) => prepared.day_tags[i] + self.line_end.len(),
+                // the heading may be at the very start of the input
+                None if s.starts_with(tag::DAY) => 0,
+                None => {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("No day heading found before position {}", lookup_from),
+                    ))
+                }
+            };
+            let (_, line) = slice(s, day, &self.line_end);
+            line
+        };
+
+        self.format.parse_day_heading(&day_line[tag::DAY.len()..])
+    }
+
+    /// A unit is a number of lines with higher level of indentation than the preceding line.  
+    /// Returns the position of the end of the unit starting at the given position.
+    /// The end of the input terminates every unit.
+    fn lookup_end_of_unit(&self, prepared: &Prepared, from: usize) -> usize {
+        let next = prepared.unit_ends.partition_point(|pos| *pos < from);
+        prepared
+            .unit_ends
+            .get(next)
+            .cloned()
+            .unwrap_or(prepared.data.len())
     }
 }
 
 /// conveniently load the birthday file to get a list of people and their birthdays
 /// see [mdlog::parser::parse_people] for details on the actual format of the file
 pub fn load_birthday_file(path: &Path) -> io::Result<Vec<Person>> {
-    let s = {
-        let mut s = String::new();
-        let mut f = File::open(path)?;
+    read_people(BufReader::new(File::open(path)?))
+}
 
-        f.read_to_string(&mut s)?;
-        s
-    };
+/// Read a birthday file line by line, e.g. from stdin; see [parse_people] for the format.  
+/// Only the lines of one person are held in memory at a time.
+pub fn read_people<R: BufRead>(reader: R) -> io::Result<Vec<Person>> {
+    let mut people = vec![];
+    let mut in_presents = false;
+    // the lines of the current person, starting with the unindented line of their name
+    let mut entry = String::new();
+    for line in reader.lines() {
+        let line = line?;
+        let starts_presents = !in_presents && line.contains(PRESENTS);
+        // lists may start unindented below the name
+        let starts_entry = line.starts_with(|c: char| !c.is_whitespace() && c != '#' && c != '-');
+        if starts_presents || starts_entry {
+            add_entry(&mut people, &entry, in_presents)?;
+            entry.clear();
+            in_presents |= starts_presents;
+        }
+        if starts_entry || !entry.is_empty() {
+            entry.push_str(&line);
+            entry.push_str(LINE_END_LINUX);
+        }
+    }
+    add_entry(&mut people, &entry, in_presents)?;
+    Ok(people)
+}
 
-    parse_people(&s)
+/// Add the person read from the given lines of a birthday file, or their presents
+fn add_entry(people: &mut Vec<Person>, entry: &str, is_presents: bool) -> io::Result<()> {
+    if entry.is_empty() {
+        return Ok(());
+    }
+    if !is_presents {
+        people.extend(parse_sections(entry, None)?);
+        return Ok(());
+    }
+
+    let presents: HashMap<String, Vec<String>> = from_yaml(entry)?;
+    for (name, presents) in presents {
+        if let Some(p) = people.iter_mut().find(|p| p.name == name) {
+            p.presents = Some(presents);
+        }
+    }
+    Ok(())
 }
 
 /// The birthday file contains people, their birthday (with or without year) and present suggestions.  
@@ -316,77 +817,302 @@ pub fn load_birthday_file(path: &Path) -> io::Result<Vec<Person>> {
 /// # }
 /// ```
 pub fn parse_people(s: &str) -> io::Result<Vec<Person>> {
-    let begin_presents = s.find("# Presents");
+    match s.find(PRESENTS) {
+        Some(pos) => parse_sections(&s[..pos], Some(&s[pos..])),
+        None => parse_sections(s, None),
+    }
+}
 
-    let birthdays = {
-        let bd_entries_end = begin_presents.unwrap_or_else(|| s.len());
-        &s[..bd_entries_end]
-    };
+/// Marks the start of the present suggestions in a birthday file
+const PRESENTS: &str = "# Presents";
 
-    let birthdays: HashMap<String, String> =
-        serde_yaml::from_str(birthdays).expect("deserialize failed");
+fn parse_sections(birthdays: &str, presents: Option<&str>) -> io::Result<Vec<Person>> {
+    let birthdays: HashMap<String, String> = from_yaml(birthdays)?;
 
     let mut people: Vec<Person> = birthdays
         .into_iter()
         .map(|(name, birthdate)| {
-            // happy path
-            match birthdate.rfind('?') {
-                None => {
-                    let date =
-                        NaiveDate::parse_from_str(&birthdate, "%d.%m.%Y").unwrap_or_else(|_| {
-                            panic!(
-                                "Failed to parse date for {}:{} please check the entry!",
-                                name, birthdate
-                            )
-                        });
-                    (name, Birthday::KnownYear(date))
-                }
-                Some(_pos) => {
-                    let mut dm = birthdate
-                        .split('.')
-                        .map(|s| u32::from_str(s).expect("Failed to parse day or month"));
-                    let d = dm.next().unwrap();
-                    let m = dm.next().unwrap();
-                    (name, Birthday::UnknownYear(m, d))
-                }
-            }
-        })
-        .map(|(name, birthday)| Person {
-            name,
-            birthday,
-            presents: None,
+            let birthday = parse_birthdate(&birthdate).map_err(|e| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Failed to parse the birthday of {}: {}", name, e),
+                )
+            })?;
+            Ok(Person {
+                name,
+                birthday,
+                presents: None,
+            })
         })
-        .collect();
+        .collect::<io::Result<_>>()?;
 
     // tack on present suggestions if there are any for this person
-    if let Some(split_pos) = begin_presents {
-        let presents = &s[split_pos..];
-        let mut presents: HashMap<String, Vec<String>> =
-            serde_yaml::from_str(presents).expect("Deserialize failed for presents");
+    if let Some(presents) = presents {
+        let mut presents: HashMap<String, Vec<String>> = from_yaml(presents)?;
         people.iter_mut().for_each(|p| {
-            let x = presents.remove(&p.name);
-            mem::replace(&mut p.presents, x);
+            p.presents = presents.remove(&p.name);
         });
     }
 
     Ok(people)
 }
 
-fn slice<'a>(s: &'a str, start: usize, delim: &'a str) -> (usize, &'a str) {
-    let pos = s[start..].find(delim).unwrap();
+fn from_yaml<T: serde::de::DeserializeOwned>(s: &str) -> io::Result<T> {
+    serde_yaml::from_str(s).map_err(|e| io::Error::new(ErrorKind::InvalidData, e.to_string()))
+}
+
+/// conveniently load the occasions file to get a list of recurring occasions
+/// see [mdlog::parser::parse_occasions] for details on the actual format of the file
+pub fn load_occasions_file(path: &Path) -> io::Result<Vec<Occasion>> {
+    let s = {
+        let mut s = String::new();
+        let mut f = File::open(path)?;
+
+        f.read_to_string(&mut s)?;
+        s
+    };
+
+    parse_occasions(&s)
+}
+
+/// The occasions file contains a list of recurring occasions.  
+/// Each entry has a ```kind``` (one of birthday, wedding, work, nameday or memorial),
+/// a ```date``` using the same format as the birthday file (```dd.mm.yyyy``` or ```dd.mm.?```)
+/// and optionally a ```label``` and the ```person``` it belongs to.
+///
+/// # Example:
+/// ```
+/// # extern crate chrono;
+/// # fn main(){
+/// use mdlog::types::{Birthday, Occasion, OccasionKind};
+/// use mdlog::parser::parse_occasions;
+/// use chrono::naive::NaiveDate;
+///
+/// let file_content = "
+/// - kind: wedding
+///   date: 12.06.2010
+///   label: Alex & Sam
+///   person: Alex
+/// - kind: memorial
+///   date: 03.04.?
+///   label: Grandpa
+///";
+/// let occasions = parse_occasions(&file_content).unwrap();
+///
+/// let correct = [
+///     Occasion{
+///         kind: OccasionKind::Wedding,
+///         date: Birthday::KnownYear(NaiveDate::from_ymd(2010,06,12)),
+///         label: Some("Alex & Sam".into()),
+///         person: Some("Alex".into()),
+///     },
+///     Occasion{
+///         kind: OccasionKind::Memorial,
+///         date: Birthday::UnknownYear(4,3),
+///         label: Some("Grandpa".into()),
+///         person: None,
+///     },
+/// ];
+///
+/// assert_eq!(&occasions, &correct);
+/// # }
+/// ```
+pub fn parse_occasions(s: &str) -> io::Result<Vec<Occasion>> {
+    #[derive(Deserialize)]
+    struct Entry {
+        kind: OccasionKind,
+        date: String,
+        label: Option<String>,
+        person: Option<String>,
+    }
+
+    let entries: Vec<Entry> = from_yaml(s)?;
+
+    entries
+        .into_iter()
+        .map(|e| {
+            Ok(Occasion {
+                kind: e.kind,
+                date: parse_birthdate(&e.date)?,
+                label: e.label,
+                person: e.person,
+            })
+        })
+        .collect()
+}
+
+/// Parse a date as ```dd.mm.yyyy``` or, if the year is not known, as ```dd.mm.?```
+fn parse_birthdate(s: &str) -> io::Result<Birthday> {
+    let invalid = |e: &dyn ToString| {
+        io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("Parsing '{}' failed with {}", s, e.to_string()),
+        )
+    };
+
+    // happy path
+    match s.rfind('?') {
+        None => NaiveDate::parse_from_str(s, "%d.%m.%Y")
+            .map(Birthday::KnownYear)
+            .map_err(|e| invalid(&e)),
+        Some(_pos) => {
+            let mut dm = s.split('.').map(u32::from_str);
+            match (dm.next(), dm.next()) {
+                // check against a leap year so the 29th of February is accepted
+                (Some(Ok(d)), Some(Ok(m))) if NaiveDate::from_ymd_opt(2000, m, d).is_some() => {
+                    Ok(Birthday::UnknownYear(m, d))
+                }
+                _ => Err(invalid(&"an invalid day or month")),
+            }
+        }
+    }
+}
+
+// slice from start up to the delim or the end of the input
+// returns the position the slice ends at along with the slice
+fn slice<'a>(s: &'a str, start: usize, delim: &str) -> (usize, &'a str) {
+    let pos = s[start..].find(delim).unwrap_or(s.len() - start);
+
+    (start + pos, &s[start..start + pos])
+}
+
+/// The input of the parser after normalising the line ends
+struct Prepared<'a> {
+    data: Cow<'a, str>,
+    /// the positions in data at which a '\r' was dropped during normalisation
+    dropped: Vec<usize>,
+    /// the position of the start of every line in data
+    line_starts: Vec<usize>,
+    /// the positions of all unit ends in data, see [MDLogParser::lookup_end_of_unit]
+    unit_ends: Vec<usize>,
+    /// the positions of all day tags in data
+    day_tags: Vec<usize>,
+}
+
+impl<'a> Prepared<'a> {
+    /// The span of the given range in data relative to the original input.  
+    /// Trailing line ends are not part of the span.
+    fn span(&self, range: Range<usize>) -> Span {
+        let end = range.start
+            + self.data[range.clone()]
+                .trim_end_matches(['\n', '\r'])
+                .len();
+        let line = self.line_starts.partition_point(|s| *s <= range.start);
+        let line_start = self.line_starts[line - 1];
+        Span {
+            file: None,
+            line,
+            column: self.data[line_start..range.start].chars().count() + 1,
+            byte_range: self.original(range.start)..self.original(end),
+        }
+    }
+
+    /// The position in the original input for the given position in data
+    fn original(&self, pos: usize) -> usize {
+        pos + self.dropped.partition_point(|d| *d < pos)
+    }
+}
+
+/// Split the data into lines along with their position; line ends are dropped
+pub(crate) fn lines(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    s.split_inclusive(LINE_END_LINUX).scan(0, |pos, l| {
+        let start = *pos;
+        *pos += l.len();
+        Some((start, l.trim_end_matches(['\n', '\r'])))
+    })
+}
+
+/// Whether the item text starts with the keyword followed by a word boundary
+fn starts_with_keyword(item: &str, keyword: &str) -> bool {
+    item.starts_with(keyword)
+        && !item[keyword.len()..]
+            .chars()
+            .next()
+            .map(char::is_alphanumeric)
+            .unwrap_or(false)
+}
+
+/// The message, time and end time (if any) of an event following its keyword.  
+/// Returns None if a time is malformed.
+pub(crate) fn event_msg(s: &str) -> Option<(&str, Option<NaiveTime>, Option<NaiveTime>)> {
+    let s = s.trim_start();
+    match s.strip_prefix(':') {
+        // straightforward event (e.g. - EVT: msg)
+        Some(msg) => Some((msg.trim_start(), None, None)),
+        // event with time ( e.g.- EVT 16:49: msg)
+        // or with time and end time (e.g. - EVT 16:49-17:30: msg)
+        None => {
+            let (h, rest) = s.split_once(':')?;
+            let (m, rest) = rest.split_once(':').unwrap_or((rest, ""));
+            let (m, end, msg) = match m.split_once('-') {
+                Some((m, end_h)) => {
+                    let (end_m, msg) = rest.split_once(':').unwrap_or((rest, ""));
+                    (m, Some(time(end_h, end_m)?), msg)
+                }
+                None => (m, None, rest),
+            };
+
+            Some((msg.trim_start(), Some(time(h, m)?), end))
+        }
+    }
+}
+
+fn time(h: &str, m: &str) -> Option<NaiveTime> {
+    let h = u32::from_str(h.trim()).ok()?;
+    let m = u32::from_str(m.trim()).ok()?;
+    NaiveTime::from_hms_opt(h, m, 0)
+}
+
+/// The canonical way to write the time of an event, e.g. ```09:05``` or ```09:05-10:30```
+pub(crate) fn event_time(start: NaiveTime, end: Option<NaiveTime>) -> String {
+    match end {
+        Some(end) => format!("{}-{}", start.format("%H:%M"), end.format("%H:%M")),
+        None => start.format("%H:%M").to_string(),
+    }
+}
+
+/// The priority and message of a task following its keyword.  
+/// The priority is an optional priority (see [is_priority]) or completion date in front of the ": ".
+/// Any other word there is part of the message.
+fn task_msg(s: &str) -> (Option<&str>, &str) {
+    let s = s.trim_start();
+    if let Some(msg) = s.strip_prefix(':') {
+        return (None, msg.trim_start());
+    }
+    match s.find(": ") {
+        Some(pos) if is_priority(&s[..pos]) || is_date(&s[..pos]) => {
+            (Some(&s[..pos]), &s[pos + ": ".len()..])
+        }
+        _ => (None, s.trim_end()),
+    }
+}
 
-    (start, &s[start..start + pos])
+/// Whether the given word is a priority, i.e. an uppercase letter optionally followed by a digit
+///
+/// # Example:
+/// ```
+/// use mdlog::parser::is_priority;
+///
+/// assert!(is_priority("A") && is_priority("B2"));
+/// assert!(!is_priority("a1") && !is_priority("A12") && !is_priority("Refactor"));
+/// ```
+pub fn is_priority(s: &str) -> bool {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(p), None, _) => p.is_ascii_uppercase(),
+        (Some(p), Some(n), None) => p.is_ascii_uppercase() && n.is_ascii_digit(),
+        _ => false,
+    }
 }
 
-// slice from after the delim  onwards
-// panics if the the token is not in the given str
-fn slice_from<'a>(s: &'a str, delim: &str) -> &'a str {
-    &s[s.find(delim).unwrap() + delim.len()..]
+fn is_date(s: &str) -> bool {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::types::WeekProblem;
 
     const EXAMPLE_DATA: &str = "
 # Week 42, 14.10.2019 - 20.10.2019
@@ -430,6 +1156,8 @@ mod test {
                 notes: vec!["b1".into(), "b2".into()],
                 date: NaiveDate::from_ymd(2019, 10, 14),
                 time: Some(NaiveTime::from_hms(16, 25, 0)),
+                end: None,
+                span: Span::default(),
             };
 
             let wed = Event {
@@ -437,6 +1165,8 @@ mod test {
                 notes: vec![],
                 date: NaiveDate::from_ymd(2019, 10, 16),
                 time: None,
+                end: None,
+                span: Span::default(),
             };
 
             let sun = Event {
@@ -444,6 +1174,8 @@ mod test {
                 notes: vec![],
                 date: NaiveDate::from_ymd(2019, 10, 20),
                 time: Some(NaiveTime::from_hms(6, 1, 0)),
+                end: None,
+                span: Span::default(),
             };
 
             [mon, wed, sun]
@@ -456,6 +1188,50 @@ mod test {
         assert_eq!(&parsed, &correct);
     }
 
+    #[test]
+    fn event_end_times() {
+        let data = "## Mon, 14.10.2019\n- EVT 9:00-10:30: a\n- EVT 11:00 - 11:15:b\n";
+        let p = MDLogParser::from_line_end(LINE_END_LINUX);
+
+        let events = p.parse_events(data).unwrap();
+        let times: Vec<_> = events
+            .iter()
+            .map(|e| (e.msg.as_str(), e.time, e.end))
+            .collect();
+        assert_eq!(
+            times,
+            vec![
+                (
+                    "a",
+                    Some(NaiveTime::from_hms(9, 0, 0)),
+                    Some(NaiveTime::from_hms(10, 30, 0))
+                ),
+                (
+                    "b",
+                    Some(NaiveTime::from_hms(11, 0, 0)),
+                    Some(NaiveTime::from_hms(11, 15, 0))
+                ),
+            ]
+        );
+
+        // malformed times are left to the linter
+        let events = p
+            .parse_events("## Mon, 14.10.2019\n- EVT 9:00-25:00: a\n- EVT standup\n- EVT 9h: b\n")
+            .unwrap();
+        let untimed: Vec<_> = events
+            .iter()
+            .map(|e| (e.msg.as_str(), e.time, e.end))
+            .collect();
+        assert_eq!(
+            untimed,
+            vec![
+                ("9:00-25:00: a", None, None),
+                ("standup", None, None),
+                ("9h: b", None, None)
+            ]
+        );
+    }
+
     #[test]
     fn tasks() {
         let correct = {
@@ -464,49 +1240,471 @@ mod test {
                 subtasks: vec![],
                 notes: vec![],
                 date: NaiveDate::from_ymd(2019, 10, 14),
-                is_done: false,
+                state: TaskState::Todo,
+                priority: None,
+                completed: None,
+                span: Span::default(),
             };
             let tue = Task {
                 msg: "d".into(),
                 subtasks: vec![Subtask {
                     msg: "d1".into(),
-                    is_done: true,
+                    state: TaskState::Done,
+                    span: Span::default(),
                 }],
                 notes: vec![],
                 date: NaiveDate::from_ymd(2019, 10, 15),
-                is_done: false,
+                state: TaskState::Todo,
+                priority: None,
+                completed: None,
+                span: Span::default(),
             };
             let thu = Task {
                 msg: "f".into(),
                 subtasks: vec![
                     Subtask {
                         msg: "f1".into(),
-                        is_done: false,
+                        state: TaskState::Todo,
+                        span: Span::default(),
                     },
                     Subtask {
                         msg: "f2".into(),
-                        is_done: false,
+                        state: TaskState::Todo,
+                        span: Span::default(),
                     },
                 ],
                 notes: vec![],
                 date: NaiveDate::from_ymd(2019, 10, 17),
-                is_done: false,
+                state: TaskState::Todo,
+                priority: Some("A1".into()),
+                completed: None,
+                span: Span::default(),
             };
             let sat = Task {
                 msg: "g".into(),
                 subtasks: vec![],
                 notes: vec![],
                 date: NaiveDate::from_ymd(2019, 10, 19),
-                is_done: true,
+                state: TaskState::Done,
+                priority: None,
+                completed: None,
+                span: Span::default(),
             };
             [mon, tue, thu, sat]
         };
 
         let p = MDLogParser::from_line_end(LINE_END_LINUX);
 
-        let tasks = p.parse_tasks(&EXAMPLE_DATA).unwrap();
+        let tasks = p.parse_tasks(EXAMPLE_DATA).unwrap();
 
         assert_eq!(&tasks, &correct);
     }
 
+    #[test]
+    fn entries() {
+        let p = MDLogParser::from_line_end(LINE_END_LINUX);
+
+        let entries = p.parse_entries(EXAMPLE_DATA).unwrap();
+        let msgs: Vec<_> = entries.iter().map(|e| (e.msg.as_str(), e.date)).collect();
+        assert_eq!(
+            msgs,
+            vec![
+                ("a", NaiveDate::from_ymd(2019, 10, 14)),
+                ("some code", NaiveDate::from_ymd(2019, 10, 18)),
+            ]
+        );
+        assert_eq!(entries[0].span.line, 5);
+        assert_eq!(entries[1].notes, vec!["```", "# code", "```"]);
+    }
+
+    #[test]
+    fn completion_dates() {
+        let data = "## Mon, 14.10.2019
+- DONE 2019-10-18: a
+- DONE B: b done:2019-10-16
+- CANCELLED 2019-10-15: c
+- TODO 2019-10-18: d
+- DONE 2019-10-18: e
+  - TODO: e1
+- TODO Refactor: f
+- TODO a1: g
+";
+        let p = MDLogParser::builder().tags(Tags::extended()).build();
+        let tasks = p.parse_tasks(data).unwrap();
+
+        let dates: Vec<_> = tasks
+            .iter()
+            .map(|t| (t.msg.as_str(), t.priority.as_deref(), t.completed))
+            .collect();
+        assert_eq!(
+            dates,
+            vec![
+                ("a", None, Some(NaiveDate::from_ymd(2019, 10, 18))),
+                (
+                    "b done:2019-10-16",
+                    Some("B"),
+                    Some(NaiveDate::from_ymd(2019, 10, 16))
+                ),
+                ("c", None, Some(NaiveDate::from_ymd(2019, 10, 15))),
+                // only closed tasks have a completion date
+                ("d", None, None),
+                ("e", None, None),
+                // words which are no priorities stay in the message
+                ("Refactor: f", None, None),
+                ("a1: g", None, None),
+            ]
+        );
+        assert_eq!(tasks[0].lead_time(), Some(4));
+    }
+
+    #[test]
+    fn weeks() {
+        let p = MDLogParser::from_line_end(LINE_END_LINUX);
+
+        let weeks = p.parse_weeks(EXAMPLE_DATA).unwrap();
+        assert_eq!(weeks.len(), 2);
+
+        let first = &weeks[0];
+        assert_eq!(first.number, 42);
+        assert_eq!(first.start, NaiveDate::from_ymd(2019, 10, 14));
+        assert_eq!(first.end, NaiveDate::from_ymd(2019, 10, 20));
+        assert_eq!(first.days.len(), 7);
+        assert_eq!(first.span.line, 2);
+        assert!(first.validate().is_empty());
+
+        let tasks = p.parse_tasks(EXAMPLE_DATA).unwrap();
+        let events = p.parse_events(EXAMPLE_DATA).unwrap();
+        assert_eq!(first.tasks(&tasks).count(), 4);
+        assert_eq!(first.events(&events).count(), 3);
+
+        let second = &weeks[1];
+        assert_eq!(second.number, 43);
+        assert!(second.days.is_empty());
+        assert_eq!(second.tasks(&tasks).count(), 0);
+    }
+
+    #[test]
+    fn invalid_weeks() {
+        let data = "# Week 41, 15.10.2019 - 20.10.2019\n## Mon, 21.10.2019\n";
+        let p = MDLogParser::from_line_end(LINE_END_LINUX);
+
+        let weeks = p.parse_weeks(data).unwrap();
+        assert_eq!(
+            weeks[0].validate(),
+            vec![
+                WeekProblem::StartNotMonday,
+                WeekProblem::WrongEnd {
+                    expected: NaiveDate::from_ymd(2019, 10, 21)
+                },
+                WeekProblem::WrongNumber { expected: 42 },
+                WeekProblem::DayOutside(NaiveDate::from_ymd(2019, 10, 21)),
+            ]
+        );
+
+        assert!(p.parse_weeks("# Week 41\n").is_err());
+    }
+
+    #[test]
+    fn custom_tags() {
+        let data = "
+## Mon, 14.10.2019
+* WAIT: a
+- CANCELLED: b
+- LATER: c
+- MTG 10:00: d
+  * d1
+- DONE: e
+  - WAITING: not a subtask
+  - DOING: e1
+- TODOS are no tasks
+";
+        let p = MDLogParser::builder()
+            .tags(Tags::extended())
+            .task("LATER", TaskState::Todo)
+            .event("MTG")
+            .build();
+
+        let tasks = p.parse_tasks(data).unwrap();
+        let states: Vec<_> = tasks.iter().map(|t| (t.msg.as_str(), t.state)).collect();
+        assert_eq!(
+            states,
+            vec![
+                ("a", TaskState::Waiting),
+                ("b", TaskState::Cancelled),
+                ("c", TaskState::Todo),
+                // open subtasks keep the task from being done
+                ("e", TaskState::Todo),
+            ]
+        );
+        assert_eq!(tasks[3].notes, vec!["WAITING: not a subtask".to_string()]);
+        assert_eq!(
+            tasks[3].subtasks,
+            vec![Subtask {
+                msg: "e1".into(),
+                state: TaskState::Doing,
+                span: Span::default(),
+            }]
+        );
+
+        let events = p.parse_events(data).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].msg, "d");
+        assert_eq!(events[0].time, Some(NaiveTime::from_hms(10, 0, 0)));
+        assert_eq!(events[0].notes, vec!["d1".to_string()]);
+
+        // without opting in, only TODO and DONE are tasks and * bullets are notes
+        let tasks = MDLogParser::builder().build().parse_tasks(data).unwrap();
+        let states: Vec<_> = tasks.iter().map(|t| (t.msg.as_str(), t.state)).collect();
+        assert_eq!(states, vec![("e", TaskState::Done)]);
+        assert_eq!(tasks[0].notes.len(), 2);
+    }
+
+    #[test]
+    fn keywords_in_messages() {
+        let tags = Tags::default();
+        assert!(tags.is_conflicting("TODO DONE: a"));
+        assert!(!tags.is_conflicting("DONE: finish TODO list"));
+        assert!(!tags.is_conflicting("TODO: undo WAITING"));
+
+        let data = "
+## Mon, 14.10.2019
+- DONE: a
+  - DONE: finish TODO list
+  - CANCELLED: the DOING column
+";
+        let p = MDLogParser::builder().tags(Tags::extended()).build();
+        let tasks = p.parse_tasks(data).unwrap();
+        let subtasks: Vec<_> = tasks[0]
+            .subtasks
+            .iter()
+            .map(|st| (st.msg.as_str(), st.state))
+            .collect();
+        assert_eq!(
+            subtasks,
+            vec![
+                ("finish TODO list", TaskState::Done),
+                ("the DOING column", TaskState::Cancelled),
+            ]
+        );
+        assert_eq!(tasks[0].state, TaskState::Done);
+
+        // conflicting subtasks are kept with the state of their first keyword
+        let data = "## Mon, 14.10.2019\n- DONE: a\n  - TODO DONE: a1\n";
+        let tasks = MDLogParser::normalizing().parse_tasks(data).unwrap();
+        assert_eq!(tasks[0].subtasks.len(), 1);
+        assert_eq!(tasks[0].subtasks[0].state, TaskState::Todo);
+        assert_eq!(tasks[0].state, TaskState::Todo);
+    }
+
+    #[test]
+    fn checkboxes() {
+        let data = "
+## Mon, 14.10.2019
+- [ ] a: with colon
+  - [x] a1
+    - [ ] a11
+- [X] b
+";
+        let p = MDLogParser::from_line_end(LINE_END_LINUX);
+
+        let tasks = p.parse_tasks(data).unwrap();
+        assert_eq!(tasks[0].msg, "a: with colon");
+        assert_eq!(tasks[0].state, TaskState::Todo);
+        assert_eq!(
+            tasks[0].subtasks,
+            vec![
+                Subtask {
+                    msg: "a1".into(),
+                    state: TaskState::Done,
+                    span: Span::default(),
+                },
+                Subtask {
+                    msg: "a11".into(),
+                    state: TaskState::Todo,
+                    span: Span::default(),
+                }
+            ]
+        );
+        assert_eq!(tasks[1].msg, "b");
+        assert_eq!(tasks[1].state, TaskState::Done);
+    }
+
+    #[test]
+    fn spans() {
+        let data = "\n## Mon, 14.10.2019\r\n- TODO: a\r\n  - DONE: a1\n\r\n- EVT 10:00: e";
+
+        for p in [
+            MDLogParser::normalizing(),
+            MDLogParser::from_line_end(LINE_END_LINUX),
+        ]
+        .iter()
+        {
+            let tasks = p.parse_tasks(data).unwrap();
+            let span = &tasks[0].span;
+            assert_eq!((span.line, span.column), (3, 1));
+            assert_eq!(&data[span.byte_range.clone()], "- TODO: a\r\n  - DONE: a1");
+
+            let span = &tasks[0].subtasks[0].span;
+            assert_eq!((span.line, span.column), (4, 3));
+            assert_eq!(&data[span.byte_range.clone()], "- DONE: a1");
+
+            let events = p.parse_events(data).unwrap();
+            let span = &events[0].span;
+            assert_eq!((span.line, span.column), (6, 1));
+            assert_eq!(&data[span.byte_range.clone()], "- EVT 10:00: e");
+        }
+    }
+
+    #[test]
+    fn unterminated_last_item() {
+        let p = MDLogParser::from_line_end(LINE_END_LINUX);
+
+        let task = "## Mon, 14.10.2019\n- TODO: a";
+        let tasks = p.parse_tasks(task).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].msg, "a");
+        assert_eq!(tasks[0].date, NaiveDate::from_ymd(2019, 10, 14));
+
+        let subtask = "## Mon, 14.10.2019\n- TODO: a\n  - DONE: a1\n  - some note";
+        let tasks = p.parse_tasks(subtask).unwrap();
+        assert_eq!(
+            tasks[0].subtasks,
+            vec![Subtask {
+                msg: "a1".into(),
+                state: TaskState::Done,
+                span: Span::default(),
+            }]
+        );
+        assert_eq!(tasks[0].notes, vec!["some note".to_string()]);
+
+        let event = "## Mon, 14.10.2019\n- EVT 06:01: b\n  - b1";
+        let events = p.parse_events(event).unwrap();
+        assert_eq!(events[0].msg, "b");
+        assert_eq!(events[0].notes, vec!["b1".to_string()]);
+
+        let event = "\n## Mon, 14.10.2019\n- EVT: c";
+        let events = p.parse_events(event).unwrap();
+        assert_eq!(events[0].msg, "c");
+        assert_eq!(events[0].time, None);
+
+        // a trailing line end on its own must not change anything
+        let tasks = p.parse_tasks("## Mon, 14.10.2019\n- DONE: a\n").unwrap();
+        assert_eq!(tasks[0].msg, "a");
+        assert_eq!(tasks[0].state, TaskState::Done);
+    }
+
+    #[test]
+    fn unterminated_windows_item() {
+        let p = MDLogParser::from_line_end(LINE_END_WINDOWS);
+
+        let tasks = p.parse_tasks("## Mon, 14.10.2019\r\n- TODO: a").unwrap();
+        assert_eq!(tasks[0].msg, "a");
+        assert_eq!(tasks[0].date, NaiveDate::from_ymd(2019, 10, 14));
+    }
+
+    #[test]
+    fn missing_day_heading() {
+        let p = MDLogParser::from_line_end(LINE_END_LINUX);
+
+        assert!(p.parse_tasks("\n- TODO: a").is_err());
+    }
+
+    #[test]
+    fn date_formats() {
+        use crate::format::Locale;
+
+        let de = LogFormat::new("%d.%m.%Y", Locale::German);
+        let iso = LogFormat::new("%Y-%m-%d", Locale::English);
+
+        for format in [LogFormat::default(), de, iso].iter() {
+            let date = NaiveDate::from_ymd(2019, 10, 14);
+            let data = format!("\n## {}\n- TODO: a\n", format.day_heading(date));
+
+            let p = MDLogParser::from_line_end(LINE_END_LINUX).with_format(format.clone());
+            let tasks = p.parse_tasks(&data).unwrap();
+
+            assert_eq!(tasks[0].date, date);
+        }
+    }
+
+    #[test]
+    fn detect_line_ends() {
+        let windows = EXAMPLE_DATA.replace(LINE_END_LINUX, LINE_END_WINDOWS);
+
+        assert_eq!(detect_line_end(EXAMPLE_DATA), LINE_END_LINUX);
+        assert_eq!(detect_line_end(&windows), LINE_END_WINDOWS);
+
+        let linux = MDLogParser::from_line_end(LINE_END_LINUX);
+        let p = MDLogParser::detect(&windows);
+
+        assert_eq!(
+            p.parse_tasks(&windows).unwrap(),
+            linux.parse_tasks(EXAMPLE_DATA).unwrap()
+        );
+        assert_eq!(
+            p.parse_events(&windows).unwrap(),
+            linux.parse_events(EXAMPLE_DATA).unwrap()
+        );
+    }
+
+    #[test]
+    fn mixed_line_ends() {
+        // every other line ends with CRLF
+        let mixed: String = EXAMPLE_DATA
+            .split(LINE_END_LINUX)
+            .enumerate()
+            .map(|(i, l)| match i % 2 {
+                0 => l.to_string() + LINE_END_WINDOWS,
+                _ => l.to_string() + LINE_END_LINUX,
+            })
+            .collect();
+
+        let linux = MDLogParser::from_line_end(LINE_END_LINUX);
+        let p = MDLogParser::normalizing();
+
+        assert_eq!(
+            p.parse_tasks(&mixed).unwrap(),
+            linux.parse_tasks(EXAMPLE_DATA).unwrap()
+        );
+        assert_eq!(
+            p.parse_events(&mixed).unwrap(),
+            linux.parse_events(EXAMPLE_DATA).unwrap()
+        );
+    }
+
+    #[test]
+    fn read_people_by_line() {
+        let data = [
+            "# Birthdays",
+            "Alex: 19.01.2001",
+            "Bob Smith: 20.12.?",
+            "",
+            "### Presents",
+            "Alex:",
+            "- Salad",
+            "  # more to come",
+            "- Moar Salad",
+            "Bob Smith: [Bazooka]",
+            "",
+        ]
+        .join("\n");
+
+        let by_name = |mut people: Vec<Person>| {
+            people.sort_by(|a, b| a.name.cmp(&b.name));
+            people
+        };
+        let people = by_name(read_people(data.as_bytes()).unwrap());
+        assert_eq!(people, by_name(parse_people(&data).unwrap()));
+        assert_eq!(
+            people[0].presents,
+            Some(vec!["Salad".to_string(), "Moar Salad".to_string()])
+        );
+        assert_eq!(people[1].presents, Some(vec!["Bazooka".to_string()]));
+
+        let invalid = ["Alex: 19.01.2001", "### Presents", "Alex: [Salad", ""].join("\n");
+        let e = read_people(invalid.as_bytes()).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+        let e = read_people("Alex: 19.13.2001\n".as_bytes()).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+    }
 }
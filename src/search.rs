@@ -0,0 +1,321 @@
+use crate::logset::LogSet;
+use crate::types::{tags, Span};
+use chrono::naive::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+/// The kinds of items searched
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    Task,
+    Event,
+    Entry,
+}
+
+impl ItemKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ItemKind::Task => "task",
+            ItemKind::Event => "event",
+            ItemKind::Entry => "entry",
+        }
+    }
+}
+
+/// A searchable item: its message, subtasks and notes
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Document {
+    pub kind: ItemKind,
+    pub date: NaiveDate,
+    pub text: String,
+    pub tags: Vec<String>,
+    pub span: Span,
+}
+
+/// What to search for.
+/// Parsed from words (all of which have to be found), ```"quoted phrases"```
+/// and ```#tags``` (all of which the item has to be tagged with).
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Query {
+    pub terms: Vec<String>,
+    pub phrases: Vec<Vec<String>>,
+    pub tags: Vec<String>,
+    /// The first day to search
+    pub from: Option<NaiveDate>,
+    /// The last day to search
+    pub to: Option<NaiveDate>,
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut query = Query::default();
+        for (i, part) in s.split('"').enumerate() {
+            // every other part is quoted
+            if i % 2 == 1 {
+                let phrase = tokens(part);
+                if !phrase.is_empty() {
+                    query.phrases.push(phrase);
+                }
+                continue;
+            }
+            for word in part.split_whitespace() {
+                match word.strip_prefix('#') {
+                    Some(_) => query
+                        .tags
+                        .extend(tags(word).into_iter().map(str::to_lowercase)),
+                    None => query.terms.extend(tokens(word)),
+                }
+            }
+        }
+        if s.matches('"').count() % 2 == 1 {
+            return Err(format!("Unbalanced quotes in {}", s));
+        }
+        Ok(query)
+    }
+}
+
+/// A document found by a query along with its score, higher is better
+#[derive(Clone, PartialEq, Debug)]
+pub struct Hit<'a> {
+    pub document: &'a Document,
+    pub score: f64,
+}
+
+/// An inverted index over the items of a log
+#[derive(Clone, Debug, Default)]
+pub struct Index {
+    documents: Vec<Document>,
+    /// The documents each term occurs in along with its positions in them
+    postings: BTreeMap<String, BTreeMap<usize, Vec<usize>>>,
+}
+
+impl Index {
+    /// Index all tasks, events and entries of the given set
+    pub fn new(set: &LogSet) -> Self {
+        let mut index = Index::default();
+        for t in set.tasks() {
+            let mut parts = vec![t.msg.as_str()];
+            parts.extend(t.subtasks.iter().map(|st| st.msg.as_str()));
+            parts.extend(t.notes.iter().map(String::as_str));
+            index.add(ItemKind::Task, t.date, &parts, &t.span);
+        }
+        for e in set.events() {
+            let mut parts = vec![e.msg.as_str()];
+            parts.extend(e.notes.iter().map(String::as_str));
+            index.add(ItemKind::Event, e.date, &parts, &e.span);
+        }
+        for e in set.entries() {
+            let mut parts = vec![e.msg.as_str()];
+            parts.extend(e.notes.iter().map(String::as_str));
+            index.add(ItemKind::Entry, e.date, &parts, &e.span);
+        }
+        index
+    }
+
+    fn add(&mut self, kind: ItemKind, date: NaiveDate, parts: &[&str], span: &Span) {
+        let text = parts.join("\n");
+        let doc = self.documents.len();
+        // a position is left out between parts, so phrases do not span them
+        let mut start = 0;
+        for part in parts {
+            let tokens = tokens(part);
+            let len = tokens.len();
+            for (pos, token) in tokens.into_iter().enumerate() {
+                self.postings
+                    .entry(token)
+                    .or_default()
+                    .entry(doc)
+                    .or_default()
+                    .push(start + pos);
+            }
+            start += len + 1;
+        }
+        self.documents.push(Document {
+            kind,
+            date,
+            tags: tags(&text).into_iter().map(str::to_lowercase).collect(),
+            text,
+            span: span.clone(),
+        });
+    }
+
+    pub fn documents(&self) -> &[Document] {
+        &self.documents
+    }
+
+    /// The documents matching the query, best first.
+    /// Documents are ranked by how often the words occur in them, rare words weighing more;
+    /// documents with the same score are ordered newest first.
+    ///
+    /// # Example:
+    /// ```
+    /// use mdlog::logset::LogSet;
+    /// use mdlog::search::Index;
+    ///
+    /// let log = [
+    ///     "## Mon, 14.10.2019",
+    ///     "- TODO: write the release notes #mdlog",
+    ///     "- EVT 10:00: release planning",
+    ///     "  notes on the release",
+    /// ]
+    /// .join("\n");
+    /// let mut set = LogSet::default();
+    /// set.add_str("log.md", &log, &mdlog::parser::MDLogParser::normalizing())
+    ///     .unwrap();
+    ///
+    /// let index = Index::new(&set);
+    /// let hits = index.search(&"release".parse().unwrap());
+    /// assert_eq!(hits.len(), 2);
+    /// // the event mentions the release twice
+    /// assert_eq!(hits[0].document.span.line, 3);
+    ///
+    /// let hits = index.search(&"\"release notes\" #mdlog".parse().unwrap());
+    /// assert_eq!(hits.len(), 1);
+    /// assert_eq!(hits[0].document.span.line, 2);
+    /// ```
+    pub fn search(&self, query: &Query) -> Vec<Hit<'_>> {
+        let words: BTreeSet<&str> = query
+            .terms
+            .iter()
+            .chain(query.phrases.iter().flatten())
+            .map(String::as_str)
+            .collect();
+
+        // the documents containing all words, or all documents if there are none
+        let mut candidates: Option<BTreeSet<usize>> = None;
+        for w in &words {
+            let docs: BTreeSet<usize> = match self.postings.get(*w) {
+                Some(docs) => docs.keys().cloned().collect(),
+                None => return vec![],
+            };
+            candidates = Some(match candidates {
+                Some(c) => c.intersection(&docs).cloned().collect(),
+                None => docs,
+            });
+        }
+        let candidates = candidates.unwrap_or_else(|| (0..self.documents.len()).collect());
+
+        let mut hits: Vec<_> = candidates
+            .into_iter()
+            .filter(|doc| self.matches(*doc, query))
+            .map(|doc| Hit {
+                document: &self.documents[doc],
+                score: words.iter().map(|w| self.weight(w, doc)).sum(),
+            })
+            .collect();
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b.document.date.cmp(&a.document.date))
+        });
+        hits
+    }
+
+    /// Whether the document is in the date range, has all tags and contains all phrases
+    fn matches(&self, doc: usize, query: &Query) -> bool {
+        let d = &self.documents[doc];
+        query.from.map(|from| d.date >= from).unwrap_or(true)
+            && query.to.map(|to| d.date <= to).unwrap_or(true)
+            && query.tags.iter().all(|t| d.tags.contains(t))
+            && query.phrases.iter().all(|p| self.contains_phrase(doc, p))
+    }
+
+    fn contains_phrase(&self, doc: usize, phrase: &[String]) -> bool {
+        let positions = |w: &String| self.postings.get(w).and_then(|docs| docs.get(&doc));
+        let first = match phrase.first().and_then(positions) {
+            Some(p) => p,
+            None => return false,
+        };
+        first.iter().any(|start| {
+            phrase.iter().enumerate().skip(1).all(|(i, w)| {
+                positions(w)
+                    .map(|p| p.binary_search(&(start + i)).is_ok())
+                    .unwrap_or(false)
+            })
+        })
+    }
+
+    /// The number of occurrences of the word in the document weighed by how rare the word is
+    fn weight(&self, word: &str, doc: usize) -> f64 {
+        let docs = match self.postings.get(word) {
+            Some(docs) => docs,
+            None => return 0.0,
+        };
+        let occurrences = docs.get(&doc).map(Vec::len).unwrap_or(0);
+        let rarity = (self.documents.len() as f64 / docs.len() as f64).ln() + 1.0;
+        occurrences as f64 * rarity
+    }
+}
+
+/// The lowercase alphanumeric words of a text
+fn tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::MDLogParser;
+
+    const EXAMPLE_DATA: &str = "## Mon, 14.10.2019
+- TODO: review the parser #mdlog
+  - write tests for the parser
+- EVT 10:00: parser review
+  bring notes
+
+## Tue, 15.10.2019
+- review of the week #work
+- DONE: parser review #mdlog
+";
+
+    #[test]
+    fn queries() {
+        let query: Query = "Parser \"the week\" #Work".parse().unwrap();
+        assert_eq!(query.terms, vec!["parser"]);
+        assert_eq!(query.phrases, vec![vec!["the", "week"]]);
+        assert_eq!(query.tags, vec!["work"]);
+        assert!("\"open".parse::<Query>().is_err());
+
+        let mut set = LogSet::default();
+        set.add_str("log.md", EXAMPLE_DATA, &MDLogParser::normalizing())
+            .unwrap();
+        let index = Index::new(&set);
+
+        let lines = |q: Query| -> Vec<usize> {
+            index
+                .search(&q)
+                .iter()
+                .map(|h| h.document.span.line)
+                .collect()
+        };
+
+        // the task mentions the parser twice, the others once, newest first
+        assert_eq!(lines("parser".parse().unwrap()), vec![2, 9, 4]);
+        assert_eq!(lines("\"parser review\"".parse().unwrap()), vec![9, 4]);
+        // the message and the note of the event are separate parts
+        assert_eq!(
+            lines("\"review bring\"".parse().unwrap()),
+            Vec::<usize>::new()
+        );
+        assert_eq!(lines("\"bring notes\"".parse().unwrap()), vec![4]);
+        assert_eq!(lines("review #mdlog".parse().unwrap()), vec![9, 2]);
+        assert_eq!(lines("#work".parse().unwrap()), vec![8]);
+        assert_eq!(
+            lines("missing parser".parse().unwrap()),
+            Vec::<usize>::new()
+        );
+
+        let mut q: Query = "review".parse().unwrap();
+        q.to = Some(NaiveDate::from_ymd(2019, 10, 14));
+        assert_eq!(lines(q), vec![2, 4]);
+    }
+}
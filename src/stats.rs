@@ -0,0 +1,370 @@
+use crate::format::LogFormat;
+use crate::types::{Event, Task};
+use chrono::naive::NaiveDate;
+use chrono::{Datelike, Duration, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// The number of busiest days listed per period
+pub const BUSIEST_DAYS: usize = 3;
+
+/// The periods statistics are grouped by
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Period {
+    Day,
+    /// ISO weeks
+    Week,
+    Month,
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "day" => Ok(Period::Day),
+            "week" => Ok(Period::Week),
+            "month" => Ok(Period::Month),
+            _ => Err(format!(
+                "Unknown period {} (expected day, week or month)",
+                s
+            )),
+        }
+    }
+}
+
+impl Period {
+    /// The first day of the period containing the date
+    pub fn start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date - Duration::days(i64::from(date.weekday().num_days_from_monday())),
+            Period::Month => NaiveDate::from_ymd(date.year(), date.month(), 1),
+        }
+    }
+
+    /// The last day of the period starting at the given date
+    pub fn end(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => start,
+            Period::Week => start + Duration::days(6),
+            Period::Month => {
+                let (y, m) = match start.month() {
+                    12 => (start.year() + 1, 1),
+                    m => (start.year(), m + 1),
+                };
+                NaiveDate::from_ymd(y, m, 1).pred()
+            }
+        }
+    }
+
+    /// A short name of the period starting at the given date, e.g. ```2019-W42``` or ```2019-10```
+    pub fn label(self, start: NaiveDate) -> String {
+        match self {
+            Period::Day => start.format("%Y-%m-%d").to_string(),
+            Period::Week => {
+                let week = start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Period::Month => start.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/// Statistics of the tasks and events of one period
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct PeriodStats {
+    pub label: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub tasks_created: usize,
+    /// Tasks done in the period, wherever they are listed
+    pub tasks_completed: usize,
+    /// The average days from creation until completion of the tasks done in the period
+    pub average_lead_time: Option<f64>,
+    /// Tasks which are neither done nor cancelled
+    pub tasks_open: usize,
+    /// The average age in days of the open tasks at the end of the period (or today if earlier).  
+    /// Tasks listed under a day after that are 0 days old.
+    pub average_open_age: Option<f64>,
+    pub subtasks: usize,
+    pub subtasks_closed: usize,
+    pub events: usize,
+    /// Events per weekday starting on monday
+    pub events_per_weekday: [usize; 7],
+    /// Events per hour of the day; events without time are not counted
+    pub events_per_hour: [usize; 24],
+    /// The days with the most tasks and events along with their number, busiest first
+    pub busiest_days: Vec<(NaiveDate, usize)>,
+}
+
+impl PeriodStats {
+    fn new(period: Period, start: NaiveDate) -> Self {
+        PeriodStats {
+            label: period.label(start),
+            start,
+            end: period.end(start),
+            tasks_created: 0,
+            tasks_completed: 0,
+            average_lead_time: None,
+            tasks_open: 0,
+            average_open_age: None,
+            subtasks: 0,
+            subtasks_closed: 0,
+            events: 0,
+            events_per_weekday: [0; 7],
+            events_per_hour: [0; 24],
+            busiest_days: vec![],
+        }
+    }
+
+    /// The share of closed subtasks; None if there are no subtasks
+    pub fn subtask_completion(&self) -> Option<f64> {
+        if self.subtasks == 0 {
+            None
+        } else {
+            Some(self.subtasks_closed as f64 / self.subtasks as f64)
+        }
+    }
+
+    /// A few lines summing up the statistics for humans
+    pub fn summary(&self, format: &LogFormat) -> String {
+        let mut lines = vec![format!(
+            "{} ({} - {})",
+            self.label,
+            format.format_date(self.start),
+            format.format_date(self.end)
+        )];
+
+        let age = self
+            .average_open_age
+            .map(|a| format!(", average age {:.1} days", a))
+            .unwrap_or_default();
+        let lead_time = self
+            .average_lead_time
+            .map(|l| format!(" (average lead time {:.1} days)", l))
+            .unwrap_or_default();
+        lines.push(format!(
+            "  tasks: {} created, {} completed{}, {} open{}",
+            self.tasks_created, self.tasks_completed, lead_time, self.tasks_open, age
+        ));
+        if let Some(ratio) = self.subtask_completion() {
+            lines.push(format!(
+                "  subtasks: {}/{} closed ({:.0}%)",
+                self.subtasks_closed,
+                self.subtasks,
+                ratio * 100.0
+            ));
+        }
+
+        lines.push(format!("  events: {}", self.events));
+        if self.events > 0 {
+            let mut wd = Weekday::Mon;
+            let mut per_weekday = vec![];
+            for n in self.events_per_weekday.iter() {
+                per_weekday.push(format!("{} {}", format.locale.weekday_name(wd), n));
+                wd = wd.succ();
+            }
+            lines.push(format!("  per weekday: {}", per_weekday.join(", ")));
+
+            let per_hour: Vec<_> = self
+                .events_per_hour
+                .iter()
+                .enumerate()
+                .filter(|(_, n)| **n > 0)
+                .map(|(h, n)| format!("{:02}h {}", h, n))
+                .collect();
+            if !per_hour.is_empty() {
+                lines.push(format!("  per hour: {}", per_hour.join(", ")));
+            }
+        }
+
+        if !self.busiest_days.is_empty() {
+            let days: Vec<_> = self
+                .busiest_days
+                .iter()
+                .map(|(d, n)| format!("{} ({})", format.day_heading(*d), n))
+                .collect();
+            lines.push(format!("  busiest days: {}", days.join(", ")));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Statistics per period of the given tasks and events, in order of the periods.  
+/// Tasks are counted in the period of the day they are listed under,
+/// done tasks as completed in the period of their completion date.
+/// Periods without any items are left out.
+pub fn stats(
+    tasks: &[Task],
+    events: &[Event],
+    period: Period,
+    today: NaiveDate,
+) -> Vec<PeriodStats> {
+    let mut periods: BTreeMap<NaiveDate, PeriodStats> = BTreeMap::new();
+    let mut items_per_day: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    let mut open_ages: BTreeMap<NaiveDate, Vec<i64>> = BTreeMap::new();
+    let mut lead_times: BTreeMap<NaiveDate, Vec<i64>> = BTreeMap::new();
+
+    for t in tasks {
+        let start = period.start(t.date);
+        let s = periods
+            .entry(start)
+            .or_insert_with(|| PeriodStats::new(period, start));
+
+        s.tasks_created += 1;
+        if !t.state.is_closed() {
+            s.tasks_open += 1;
+            let until = if s.end < today { s.end } else { today };
+            open_ages
+                .entry(start)
+                .or_default()
+                .push((until - t.date).num_days().max(0));
+        }
+        s.subtasks += t.subtasks.len();
+        s.subtasks_closed += t.subtasks.iter().filter(|st| st.state.is_closed()).count();
+        *items_per_day.entry(t.date).or_default() += 1;
+
+        if t.state.is_done() {
+            let done = period.start(t.completed.unwrap_or(t.date));
+            periods
+                .entry(done)
+                .or_insert_with(|| PeriodStats::new(period, done))
+                .tasks_completed += 1;
+            lead_times
+                .entry(done)
+                .or_default()
+                .push(t.lead_time().unwrap_or(0));
+        }
+    }
+
+    for e in events {
+        let start = period.start(e.date);
+        let s = periods
+            .entry(start)
+            .or_insert_with(|| PeriodStats::new(period, start));
+
+        s.events += 1;
+        s.events_per_weekday[e.date.weekday().num_days_from_monday() as usize] += 1;
+        if let Some(time) = e.time {
+            s.events_per_hour[time.hour() as usize] += 1;
+        }
+        *items_per_day.entry(e.date).or_default() += 1;
+    }
+
+    for (start, s) in periods.iter_mut() {
+        if let Some(ages) = open_ages.get(start) {
+            s.average_open_age = Some(average(ages));
+        }
+        if let Some(lead_times) = lead_times.get(start) {
+            s.average_lead_time = Some(average(lead_times));
+        }
+
+        let mut days: Vec<_> = items_per_day
+            .range(s.start..=s.end)
+            .map(|(d, n)| (*d, *n))
+            .collect();
+        // stable, so days with the same number stay in order
+        days.sort_by_key(|(_, n)| Reverse(*n));
+        days.truncate(BUSIEST_DAYS);
+        s.busiest_days = days;
+    }
+
+    periods.into_values().collect()
+}
+
+fn average(days: &[i64]) -> f64 {
+    days.iter().sum::<i64>() as f64 / days.len() as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::{MDLogParser, Tags};
+
+    const EXAMPLE_DATA: &str = "## Mon, 14.10.2019
+- EVT 09:00: standup
+- EVT 09:30: review
+- TODO: a
+  - DONE: a1
+  - TODO: a2
+- DONE: b
+- DONE 2019-10-29: g
+
+## Wed, 16.10.2019
+- EVT: lunch
+- CANCELLED: c
+- WAIT: d
+
+## Fri, 01.11.2019
+- EVT 16:00: retro
+- TODO: e
+";
+
+    #[test]
+    fn weeks_and_months() {
+        let p = MDLogParser::builder()
+            .normalize(true)
+            .tags(Tags::extended())
+            .build();
+        let tasks = p.parse_tasks(EXAMPLE_DATA).unwrap();
+        let events = p.parse_events(EXAMPLE_DATA).unwrap();
+        let today = NaiveDate::from_ymd(2019, 11, 2);
+
+        let weeks = stats(&tasks, &events, Period::Week, today);
+        assert_eq!(weeks.len(), 2);
+
+        let w = &weeks[0];
+        assert_eq!(w.label, "2019-W42");
+        assert_eq!(w.end, NaiveDate::from_ymd(2019, 10, 20));
+        assert_eq!(
+            (w.tasks_created, w.tasks_completed, w.tasks_open),
+            (5, 1, 2)
+        );
+        assert_eq!(w.average_lead_time, Some(0.0));
+        // a is 6 days old at the end of the week, d 4
+        assert_eq!(w.average_open_age, Some(5.0));
+        assert_eq!(w.subtask_completion(), Some(0.5));
+        assert_eq!(w.events, 3);
+        assert_eq!(w.events_per_weekday, [2, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(w.events_per_hour[9], 2);
+        assert_eq!(
+            w.busiest_days,
+            vec![
+                (NaiveDate::from_ymd(2019, 10, 14), 5),
+                (NaiveDate::from_ymd(2019, 10, 16), 3),
+            ]
+        );
+
+        // g was done two weeks after it was listed
+        assert_eq!(weeks[1].tasks_completed, 1);
+        assert_eq!(weeks[1].average_lead_time, Some(15.0));
+        // e is only a day old today
+        assert_eq!(weeks[1].average_open_age, Some(1.0));
+
+        let months = stats(&tasks, &events, Period::Month, today);
+        let labels: Vec<_> = months.iter().map(|m| m.label.as_str()).collect();
+        assert_eq!(labels, vec!["2019-10", "2019-11"]);
+        assert_eq!(months[0].end, NaiveDate::from_ymd(2019, 10, 31));
+        assert_eq!(months[0].average_open_age, Some(16.0));
+    }
+
+    #[test]
+    fn future_tasks() {
+        let p = MDLogParser::builder()
+            .normalize(true)
+            .tags(Tags::extended())
+            .build();
+        let tasks = p.parse_tasks(EXAMPLE_DATA).unwrap();
+        let today = NaiveDate::from_ymd(2019, 10, 15);
+
+        let weeks = stats(&tasks, &[], Period::Week, today);
+        // a is a day old today, d is only planned for tomorrow
+        assert_eq!(weeks[0].average_open_age, Some(0.5));
+        assert_eq!(weeks[1].average_open_age, Some(0.0));
+    }
+}
@@ -0,0 +1,244 @@
+use crate::parser::{tag, MDLogParser};
+use crate::types::{Entry, Event, Span, Task};
+use serde::{Deserialize, Serialize};
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+
+/// A top-level item of a log
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Item {
+    Task(Task),
+    Event(Event),
+    Entry(Entry),
+}
+
+/// Read the items of a log line by line, e.g. from stdin.
+/// Each item is yielded as soon as its unit ends, so only the current unit and day heading
+/// are held in memory. Items are the same as those of [MDLogParser::parse_tasks],
+/// [MDLogParser::parse_events] and [MDLogParser::parse_entries], in order of the input.
+///
+/// # Example:
+/// ```
+/// use mdlog::parser::MDLogParser;
+/// use mdlog::stream::{items, Item};
+///
+/// let log = "## Mon, 14.10.2019\n- TODO: a\n  - DONE: a1\n- EVT 10:00: b\n";
+/// let p = MDLogParser::normalizing();
+/// let items: Vec<_> = items(&p, log.as_bytes()).collect::<Result<_, _>>().unwrap();
+///
+/// match &items[..] {
+///     [Item::Task(t), Item::Event(e)] => {
+///         assert_eq!(t.subtasks[0].span.line, 3);
+///         assert_eq!(e.msg, "b");
+///     }
+///     _ => panic!("expected a task and an event"),
+/// }
+/// ```
+pub fn items<R: BufRead>(parser: &MDLogParser, reader: R) -> Items<'_, R> {
+    Items {
+        parser,
+        reader,
+        unit_ends: parser
+            .unit_ends()
+            .iter()
+            .map(|e| e[parser.line_end().len()..].to_string())
+            .collect(),
+        day: None,
+        unit: String::new(),
+        unit_line: 0,
+        unit_pos: 0,
+        after_line_end: false,
+        line: 1,
+        pos: 0,
+        pending: VecDeque::new(),
+    }
+}
+
+/// The iterator returned by [items]
+pub struct Items<'a, R> {
+    parser: &'a MDLogParser,
+    reader: R,
+    /// what a line starts with if it ends the unit before it
+    unit_ends: Vec<String>,
+    /// the line of the current day heading
+    day: Option<String>,
+    /// the lines of the current unit
+    unit: String,
+    /// the line and position the current unit starts at
+    unit_line: usize,
+    unit_pos: usize,
+    /// whether the last line read ended with the line end of the parser
+    after_line_end: bool,
+    /// the line and position of the next line
+    line: usize,
+    pos: usize,
+    pending: VecDeque<Item>,
+}
+
+impl<'a, R: BufRead> Iterator for Items<'a, R> {
+    type Item = io::Result<Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        while self.pending.is_empty() {
+            line.clear();
+            let read = match self.reader.read_line(&mut line) {
+                Ok(read) => read,
+                Err(e) => return Some(Err(e)),
+            };
+            let ends_unit = read == 0
+                || (self.after_line_end && self.unit_ends.iter().any(|e| line.starts_with(e)));
+            if ends_unit && !self.unit.is_empty() {
+                if let Err(e) = self.parse_unit() {
+                    return Some(Err(e));
+                }
+            }
+            if read == 0 {
+                break;
+            }
+
+            // a day heading may also be the very first line
+            let at_line_start = self.after_line_end || self.line == 1;
+            if at_line_start && line.starts_with(tag::DAY) {
+                self.day = Some(line.clone());
+            }
+            let is_item = self
+                .parser
+                .tags()
+                .items
+                .iter()
+                .any(|i| line.starts_with(i.as_str()));
+            if self.after_line_end && is_item {
+                self.unit_line = self.line;
+                self.unit_pos = self.pos;
+                self.unit.push_str(&line);
+            } else if !self.unit.is_empty() {
+                self.unit.push_str(&line);
+            }
+
+            self.after_line_end = line.ends_with(self.parser.line_end());
+            self.line += 1;
+            self.pos += read;
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+impl<'a, R> Items<'a, R> {
+    /// Parse the current unit below its day heading and move its spans to where it was read
+    fn parse_unit(&mut self) -> io::Result<()> {
+        // without a day heading the parser reports the item just as it would in the whole log
+        let heading = self
+            .day
+            .clone()
+            .unwrap_or_else(|| self.parser.line_end().to_string());
+        let chunk = heading.clone() + &self.unit;
+        self.unit.clear();
+
+        let with_line =
+            |e: io::Error| io::Error::new(e.kind(), format!("line {}: {}", self.unit_line, e));
+        let tasks = self.parser.parse_tasks(&chunk).map_err(with_line)?;
+        let events = self.parser.parse_events(&chunk).map_err(with_line)?;
+        let entries = self.parser.parse_entries(&chunk).map_err(with_line)?;
+
+        let (line, pos) = (self.unit_line, self.unit_pos);
+        let shift = |span: &mut Span| {
+            // the heading is the first line of the chunk, the unit starts on the second
+            span.line += line - 2;
+            span.byte_range = pos + span.byte_range.start - heading.len()
+                ..pos + span.byte_range.end - heading.len();
+        };
+        for mut t in tasks {
+            shift(&mut t.span);
+            t.subtasks.iter_mut().for_each(|st| shift(&mut st.span));
+            self.pending.push_back(Item::Task(t));
+        }
+        for mut e in events {
+            shift(&mut e.span);
+            self.pending.push_back(Item::Event(e));
+        }
+        for mut e in entries {
+            shift(&mut e.span);
+            self.pending.push_back(Item::Entry(e));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_DATA: &str = "# Week 42, 14.10.2019 - 20.10.2019
+
+## Mon, 14.10.2019
+- some entry
+  with a note
+- TODO: a
+  - DONE: a1
+  a note
+
+text between units
+- EVT 09:00-10:00: b
+
+## Tue, 15.10.2019
+- DONE 2019-10-16: c
+* EVT: d
+";
+
+    #[test]
+    fn same_as_whole_input() {
+        for data in &[EXAMPLE_DATA.to_string(), EXAMPLE_DATA.replace('\n', "\r\n")] {
+            let p = MDLogParser::normalizing();
+            let items: Vec<_> = items(&p, data.as_bytes())
+                .collect::<io::Result<_>>()
+                .unwrap();
+
+            let mut tasks = vec![];
+            let mut events = vec![];
+            let mut entries = vec![];
+            for item in items {
+                match item {
+                    Item::Task(t) => tasks.push(t),
+                    Item::Event(e) => events.push(e),
+                    Item::Entry(e) => entries.push(e),
+                }
+            }
+
+            let spans = |tasks: &[Task]| -> Vec<Span> {
+                tasks
+                    .iter()
+                    .flat_map(|t| {
+                        std::iter::once(t.span.clone())
+                            .chain(t.subtasks.iter().map(|st| st.span.clone()))
+                    })
+                    .collect()
+            };
+            let expected = p.parse_tasks(data).unwrap();
+            assert_eq!(tasks, expected);
+            assert_eq!(spans(&tasks), spans(&expected));
+
+            let expected = p.parse_events(data).unwrap();
+            assert_eq!(events, expected);
+            let spans: Vec<_> = events.iter().map(|e| e.span.clone()).collect();
+            assert_eq!(
+                spans,
+                expected.iter().map(|e| e.span.clone()).collect::<Vec<_>>()
+            );
+
+            let expected = p.parse_entries(data).unwrap();
+            assert_eq!(entries, expected);
+            assert_eq!(entries[0].span, expected[0].span);
+        }
+    }
+
+    #[test]
+    fn missing_day() {
+        let p = MDLogParser::normalizing();
+        let mut items = items(&p, "\n- TODO: a\n".as_bytes());
+        let e = items.next().unwrap().unwrap_err();
+        assert!(e.to_string().starts_with("line 2: "));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::stats::Period;
+use crate::types::Event;
+use chrono::naive::{NaiveDate, NaiveTime};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+
+/// The tag time spent on events without tags is booked on
+pub const UNTAGGED: &str = "";
+
+/// The end of the event at the given index: its own end if given,
+/// otherwise the start of the next timed event on the same day.  
+/// Events are expected in the order they appear in the log.
+pub fn event_end(events: &[Event], index: usize) -> Option<NaiveTime> {
+    let event = &events[index];
+    let start = event.time?;
+    if event.end.is_some() {
+        return event.end;
+    }
+    events[index + 1..]
+        .iter()
+        .take_while(|e| e.date == event.date)
+        .filter_map(|e| e.time)
+        .find(|t| *t > start)
+}
+
+/// The time spent on the event at the given index, see [event_end].  
+/// There is none for events ending before they start (e.g. past midnight).
+pub fn duration(events: &[Event], index: usize) -> Option<Duration> {
+    let d = event_end(events, index)? - events[index].time?;
+    Some(d).filter(|d| *d > Duration::zero())
+}
+
+/// The time spent on every event with a [duration]
+pub fn durations(events: &[Event]) -> Vec<(&Event, Duration)> {
+    events
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| Some((e, duration(events, i)?)))
+        .collect()
+}
+
+/// The time spent in one period
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct TimesheetPeriod {
+    pub label: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Minutes per tag; events with several tags count for each of them
+    pub minutes_per_tag: BTreeMap<String, i64>,
+    /// Minutes of all events, each counted once
+    pub total_minutes: i64,
+}
+
+impl TimesheetPeriod {
+    /// The time per tag as lines of a table
+    pub fn summary(&self) -> String {
+        let width = self
+            .minutes_per_tag
+            .keys()
+            .map(|t| tag_name(t).chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("total".len());
+
+        let mut lines = vec![self.label.clone()];
+        for (tag, minutes) in &self.minutes_per_tag {
+            lines.push(format!(
+                "  {:width$}  {:>6}",
+                tag_name(tag),
+                hours(*minutes),
+                width = width
+            ));
+        }
+        lines.push(format!(
+            "  {:width$}  {:>6}",
+            "total",
+            hours(self.total_minutes),
+            width = width
+        ));
+        lines.join("\n")
+    }
+}
+
+fn tag_name(tag: &str) -> String {
+    if tag == UNTAGGED {
+        "(untagged)".into()
+    } else {
+        format!("#{}", tag)
+    }
+}
+
+/// Minutes written as ```H:MM```
+fn hours(minutes: i64) -> String {
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// The time spent per tag and period, in order of the periods.  
+/// Periods without any time spent are left out.
+///
+/// # Example:
+/// ```
+/// use mdlog::parser::MDLogParser;
+/// use mdlog::stats::Period;
+/// use mdlog::timesheet::timesheet;
+///
+/// let log = "## Mon, 14.10.2019
+/// - EVT 09:00: standup #team
+/// - EVT 09:15: coding #mdlog
+/// - EVT 12:00-12:30: lunch
+/// - EVT 13:00-15:00: coding #mdlog
+/// ";
+/// let events = MDLogParser::normalizing().parse_events(log).unwrap();
+///
+/// let sheet = timesheet(&events, Period::Day);
+/// assert_eq!(
+///     sheet[0].summary(),
+///     "2019-10-14
+///   (untagged)    0:30
+///   #mdlog        4:45
+///   #team         0:15
+///   total         5:30"
+/// );
+/// ```
+pub fn timesheet(events: &[Event], period: Period) -> Vec<TimesheetPeriod> {
+    let mut periods: BTreeMap<NaiveDate, TimesheetPeriod> = BTreeMap::new();
+
+    for (e, duration) in durations(events) {
+        let start = period.start(e.date);
+        let p = periods.entry(start).or_insert_with(|| TimesheetPeriod {
+            label: period.label(start),
+            start,
+            end: period.end(start),
+            minutes_per_tag: BTreeMap::new(),
+            total_minutes: 0,
+        });
+
+        let minutes = duration.num_minutes();
+        p.total_minutes += minutes;
+
+        let mut tags = e.tags();
+        if tags.is_empty() {
+            tags.push(UNTAGGED);
+        }
+        tags.sort_unstable();
+        tags.dedup();
+        for tag in tags {
+            *p.minutes_per_tag.entry(tag.to_string()).or_default() += minutes;
+        }
+    }
+
+    periods.into_values().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::MDLogParser;
+
+    const EXAMPLE_DATA: &str = "## Mon, 14.10.2019
+- EVT 09:00: standup #team #mdlog
+- EVT: no time #team
+- EVT 09:30: review #mdlog
+- EVT 10:00-09:00: broken #team
+
+## Tue, 15.10.2019
+- EVT 10:00-11:00: planning #team
+- EVT 17:00: open end #mdlog
+";
+
+    #[test]
+    fn weeks() {
+        let events = MDLogParser::normalizing()
+            .parse_events(EXAMPLE_DATA)
+            .unwrap();
+
+        let ends: Vec<_> = (0..events.len())
+            .map(|i| event_end(&events, i).map(|t| t.to_string()))
+            .collect();
+        assert_eq!(
+            ends,
+            vec![
+                Some("09:30:00".into()),
+                None,
+                Some("10:00:00".into()),
+                Some("09:00:00".into()),
+                Some("11:00:00".into()),
+                None,
+            ]
+        );
+
+        let sheet = timesheet(&events, Period::Week);
+        assert_eq!(sheet.len(), 1);
+        assert_eq!(sheet[0].label, "2019-W42");
+        assert_eq!(sheet[0].minutes_per_tag["team"], 90);
+        assert_eq!(sheet[0].minutes_per_tag["mdlog"], 60);
+        assert_eq!(sheet[0].total_minutes, 120);
+
+        assert_eq!(timesheet(&events, Period::Day).len(), 2);
+    }
+}
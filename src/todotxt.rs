@@ -0,0 +1,401 @@
+use crate::format::LogFormat;
+use crate::parser::{Tags, TaskStyle};
+use crate::types::{Subtask, Task, TaskState};
+use crate::writer;
+use chrono::naive::NaiveDate;
+
+use std::fmt;
+use std::io::{self, ErrorKind};
+use std::str::FromStr;
+
+const DATE_FMT: &str = "%Y-%m-%d";
+/// The key of the numbers linking the subtasks of tasks without an ```id:``` marker of their own
+const KEY: &str = "mdlog-id";
+
+/// A single line of a todo.txt file
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct TodoTxtTask {
+    pub done: bool,
+    /// ```A``` to ```Z```
+    pub priority: Option<char>,
+    pub completed: Option<NaiveDate>,
+    pub created: Option<NaiveDate>,
+    /// The rest of the line including ```+projects```, ```@contexts``` and ```key:value``` pairs
+    pub description: String,
+}
+
+impl TodoTxtTask {
+    /// The words of the description starting with ```+```, without the ```+```
+    pub fn projects(&self) -> Vec<&str> {
+        self.words_with_prefix('+')
+    }
+
+    /// The words of the description starting with ```@```, without the ```@```
+    pub fn contexts(&self) -> Vec<&str> {
+        self.words_with_prefix('@')
+    }
+
+    /// The value of the first ```key:value``` pair with the given key
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.description
+            .split_whitespace()
+            .filter_map(|w| w.split_once(':'))
+            .find(|(k, v)| *k == key && !v.is_empty())
+            .map(|(_, v)| v)
+    }
+
+    fn words_with_prefix(&self, prefix: char) -> Vec<&str> {
+        self.description
+            .split_whitespace()
+            .filter_map(|w| w.strip_prefix(prefix))
+            .filter(|w| !w.is_empty())
+            .collect()
+    }
+}
+
+impl FromStr for TodoTxtTask {
+    type Err = io::Error;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut task = TodoTxtTask::default();
+        let mut rest = line.trim();
+        if rest.is_empty() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "Empty todo.txt line",
+            ));
+        }
+
+        if let Some(r) = rest.strip_prefix("x ") {
+            task.done = true;
+            rest = r.trim_start();
+        }
+
+        let bytes = rest.as_bytes();
+        if bytes.len() >= 4 && bytes[0] == b'(' && bytes[2] == b')' && bytes[3] == b' ' {
+            let p = bytes[1] as char;
+            if p.is_ascii_uppercase() {
+                task.priority = Some(p);
+                rest = rest[4..].trim_start();
+            }
+        }
+
+        let mut dates = vec![];
+        while dates.len() < 2 {
+            let (word, r) = rest.split_once(' ').unwrap_or((rest, ""));
+            match NaiveDate::parse_from_str(word, DATE_FMT) {
+                Ok(d) => dates.push(d),
+                Err(_) => break,
+            }
+            rest = r.trim_start();
+        }
+        match (task.done, dates.as_slice()) {
+            (true, [completed, created]) => {
+                task.completed = Some(*completed);
+                task.created = Some(*created);
+            }
+            (true, [completed]) => task.completed = Some(*completed),
+            (false, [created, ..]) => task.created = Some(*created),
+            _ => (),
+        }
+
+        task.description = rest.to_string();
+        Ok(task)
+    }
+}
+
+impl fmt::Display for TodoTxtTask {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.done {
+            write!(f, "x ")?;
+        }
+        if let Some(p) = self.priority {
+            write!(f, "({}) ", p)?;
+        }
+        if self.done {
+            if let Some(d) = self.completed {
+                write!(f, "{} ", d.format(DATE_FMT))?;
+            }
+        }
+        if let Some(d) = self.created {
+            write!(f, "{} ", d.format(DATE_FMT))?;
+        }
+        write!(f, "{}", self.description)
+    }
+}
+
+/// Parse all non-empty lines of a todo.txt file
+pub fn parse(s: &str) -> io::Result<Vec<TodoTxtTask>> {
+    s.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(TodoTxtTask::from_str)
+        .collect()
+}
+
+/// Convert tasks to todo.txt.  
+/// - the date of a task is its creation date, and its completion date if it is closed
+///   without a completion date of its own
+/// - single letter priorities become ```(A)```, all others a ```pri:``` pair
+/// - tags (```#tag```) become projects (```+tag```), tags starting with ```@``` (```#@home```) contexts
+/// - subtasks follow their task with a ```parent:``` pair referring to the ```id:``` of the task;
+///   tasks without an ```id:``` marker of their own get a numbered ```mdlog-id:``` pair
+///
+/// # Example:
+/// ```
+/// use mdlog::parser::MDLogParser;
+/// use mdlog::todotxt;
+///
+/// let log = "## Mon, 14.10.2019\n- TODO A: write report #work #@office\n  - DONE: outline\n";
+/// let tasks = MDLogParser::normalizing().parse_tasks(log).unwrap();
+///
+/// let lines: Vec<_> = todotxt::from_tasks(&tasks).iter().map(|t| t.to_string()).collect();
+/// assert_eq!(
+///     lines,
+///     vec![
+///         "(A) 2019-10-14 write report +work @office mdlog-id:1",
+///         "x 2019-10-14 2019-10-14 outline parent:1",
+///     ]
+/// );
+/// ```
+pub fn from_tasks(tasks: &[Task]) -> Vec<TodoTxtTask> {
+    let own_ids: Vec<&str> = tasks.iter().filter_map(Task::id).collect();
+    let mut numbers = (1..).map(|n: usize| n.to_string());
+    let mut todos = vec![];
+    for t in tasks {
+        let mut description = to_todotxt_msg(&t.msg);
+        let priority = match t.priority.as_deref().map(|p| (p, p.parse::<char>())) {
+            Some((_, Ok(p))) if p.is_ascii_uppercase() => Some(p),
+            Some((p, _)) => {
+                description.push_str(&format!(" pri:{}", p));
+                None
+            }
+            None => None,
+        };
+        let id = match t.id() {
+            Some(id) => id.to_string(),
+            None if t.subtasks.is_empty() => String::new(),
+            None => {
+                let id = numbers
+                    .find(|n| !own_ids.contains(&n.as_str()))
+                    .unwrap_or_default();
+                description.push_str(&format!(" {}:{}", KEY, id));
+                id
+            }
+        };
+
+        let completed = t.completed.unwrap_or(t.date);
+        todos.push(todo(t.state, priority, t.date, completed, description));
+        for st in &t.subtasks {
+            let description = format!("{} parent:{}", to_todotxt_msg(&st.msg), id);
+            todos.push(todo(st.state, None, t.date, completed, description));
+        }
+    }
+    todos
+}
+
+fn todo(
+    state: TaskState,
+    priority: Option<char>,
+    created: NaiveDate,
+    completed: NaiveDate,
+    description: String,
+) -> TodoTxtTask {
+    let done = state.is_closed();
+    TodoTxtTask {
+        done,
+        priority,
+        completed: if done { Some(completed) } else { None },
+        created: Some(created),
+        description,
+    }
+}
+
+/// Convert todo.txt tasks to tasks, the reverse of [from_tasks].  
+/// Tasks are dated by their creation date, their completion date or the given default date.
+/// A completion date on the day the task is dated by is implied and not set.
+/// Tasks with a ```parent:``` pair become subtasks of the task with the matching ```id:```
+/// or ```mdlog-id:```. The ```mdlog-id:``` pairs are dropped, ```id:``` markers are kept.
+pub fn to_tasks(todos: &[TodoTxtTask], default_date: NaiveDate) -> Vec<Task> {
+    let is_parent_of = |parent: &TodoTxtTask, child: &TodoTxtTask| {
+        let id = parent.value(KEY).or_else(|| parent.value("id"));
+        id.is_some() && id == child.value("parent")
+    };
+
+    todos
+        .iter()
+        .filter(|t| !todos.iter().any(|p| is_parent_of(p, t)))
+        .map(|t| {
+            let subtasks = todos
+                .iter()
+                .filter(|st| is_parent_of(t, st))
+                .map(|st| Subtask {
+                    msg: from_todotxt_msg(&st.description),
+                    state: state(st),
+                    span: Default::default(),
+                })
+                .collect();
+            let priority = t
+                .priority
+                .map(|p| p.to_string())
+                .or_else(|| t.value("pri").map(String::from));
+
+            let date = t.created.or(t.completed).unwrap_or(default_date);
+            Task {
+                msg: from_todotxt_msg(&t.description),
+                subtasks,
+                notes: vec![],
+                date,
+                state: state(t),
+                priority,
+                completed: t.completed.filter(|c| *c != date),
+                span: Default::default(),
+            }
+        })
+        .collect()
+}
+
+/// Add the given todo.txt tasks to a log, each under the heading of its day
+pub fn import(
+    log_data: &str,
+    todos: &[TodoTxtTask],
+    format: &LogFormat,
+    tags: &Tags,
+    default_date: NaiveDate,
+) -> String {
+    to_tasks(todos, default_date)
+        .iter()
+        .fold(log_data.to_string(), |log, t| {
+            let lines = writer::task_lines(t, tags, TaskStyle::Keyword);
+            writer::insert_under_day(&log, format, t.date, &lines)
+        })
+}
+
+fn state(todo: &TodoTxtTask) -> TaskState {
+    if todo.done {
+        TaskState::Done
+    } else {
+        TaskState::Todo
+    }
+}
+
+fn to_todotxt_msg(msg: &str) -> String {
+    msg.split_whitespace()
+        .map(|w| match w.strip_prefix('#') {
+            Some(t) if t.starts_with('@') => t.to_string(),
+            Some(t) if !t.is_empty() => format!("+{}", t),
+            _ => w.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn from_todotxt_msg(description: &str) -> String {
+    description
+        .split_whitespace()
+        .filter(|w| !is_linking_pair(w))
+        .map(|w| match w.chars().next() {
+            Some('+') if w.len() > 1 => format!("#{}", &w[1..]),
+            Some('@') if w.len() > 1 => format!("#{}", w),
+            _ => w.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether the word is one of the pairs written by [from_tasks] which have no place in a message
+fn is_linking_pair(word: &str) -> bool {
+    word.split_once(':')
+        .map(|(k, _)| [KEY, "parent", "pri"].contains(&k))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::MDLogParser;
+
+    #[test]
+    fn parse_lines() {
+        let todos = parse(
+            "(A) 2019-10-14 call mom +family @phone due:2019-10-20
+
+x 2019-10-18 2019-10-14 pay bills
+x 2019-10-18 done without creation date
+no date at all",
+        )
+        .unwrap();
+
+        assert_eq!(todos.len(), 4);
+        assert_eq!(todos[0].priority, Some('A'));
+        assert_eq!(todos[0].created, Some(NaiveDate::from_ymd(2019, 10, 14)));
+        assert_eq!(todos[0].projects(), vec!["family"]);
+        assert_eq!(todos[0].contexts(), vec!["phone"]);
+        assert_eq!(todos[0].value("due"), Some("2019-10-20"));
+        assert!(todos[1].done);
+        assert_eq!(todos[1].completed, Some(NaiveDate::from_ymd(2019, 10, 18)));
+        assert_eq!(todos[1].created, Some(NaiveDate::from_ymd(2019, 10, 14)));
+        assert_eq!(todos[2].created, None);
+        assert_eq!(todos[3].description, "no date at all");
+
+        let lines: Vec<_> = todos.iter().map(|t| t.to_string()).collect();
+        assert_eq!(
+            lines[0],
+            "(A) 2019-10-14 call mom +family @phone due:2019-10-20"
+        );
+        assert_eq!(lines[1], "x 2019-10-18 2019-10-14 pay bills");
+    }
+
+    #[test]
+    fn round_trip() {
+        let log = "## Mon, 14.10.2019
+- TODO A: a #work
+  - DONE: a1
+  - TODO: a2 #@home
+- DONE B2: b
+- DONE 2019-10-16: c
+";
+        let p = MDLogParser::normalizing();
+        let tasks = p.parse_tasks(log).unwrap();
+        let todos = from_tasks(&tasks);
+        assert_eq!(todos[3].description, "b pri:B2");
+
+        let today = NaiveDate::from_ymd(2019, 10, 20);
+        assert_eq!(to_tasks(&todos, today), tasks);
+
+        let imported = import("", &todos, &LogFormat::default(), &Tags::default(), today);
+        assert_eq!(imported, log);
+    }
+
+    #[test]
+    fn ids() {
+        let log = "## Mon, 14.10.2019
+- TODO: a id:1
+  - TODO: a1
+- TODO: b
+  - TODO: b1
+- TODO: c id:report
+  - TODO: c1
+";
+        let p = MDLogParser::normalizing();
+        let tasks = p.parse_tasks(log).unwrap();
+        let lines: Vec<_> = from_tasks(&tasks).iter().map(|t| t.to_string()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "2019-10-14 a id:1",
+                "2019-10-14 a1 parent:1",
+                "2019-10-14 b mdlog-id:2",
+                "2019-10-14 b1 parent:2",
+                "2019-10-14 c id:report",
+                "2019-10-14 c1 parent:report",
+            ]
+        );
+
+        let tasks = to_tasks(&from_tasks(&tasks), NaiveDate::from_ymd(2019, 10, 20));
+        let msgs: Vec<_> = tasks.iter().map(|t| t.msg.as_str()).collect();
+        assert_eq!(msgs, vec!["a id:1", "b", "c id:report"]);
+        assert!(tasks.iter().all(|t| t.subtasks.len() == 1));
+        assert_eq!(tasks[0].id(), Some("1"));
+        assert_eq!(tasks[2].id(), Some("report"));
+    }
+}
@@ -0,0 +1,320 @@
+use crate::format::LogFormat;
+use crate::parser::{self, tag, Tags, TaskStyle};
+use crate::types::{marker_date, Span, Task, TaskState};
+use chrono::naive::NaiveDate;
+
+use std::io::{self, ErrorKind};
+
+/// The MDLog lines of a task and its subtasks, without a line end.  
+/// States without a tag in the given style fall back to their keyword.
+///
+/// # Example:
+/// ```
+/// # extern crate chrono;
+/// # fn main(){
+/// use chrono::naive::NaiveDate;
+/// use mdlog::parser::{Tags, TaskStyle};
+/// use mdlog::types::{Span, Subtask, Task, TaskState};
+/// use mdlog::writer::task_lines;
+///
+/// let task = Task {
+///     msg: "a".into(),
+///     subtasks: vec![Subtask { msg: "a1".into(), state: TaskState::Done, span: Span::default() }],
+///     notes: vec!["some note".into()],
+///     date: NaiveDate::from_ymd(2019, 10, 14),
+///     state: TaskState::Todo,
+///     priority: Some("A".into()),
+///     completed: None,
+///     span: Span::default(),
+/// };
+///
+/// assert_eq!(
+///     task_lines(&task, &Tags::default(), TaskStyle::Keyword),
+///     vec!["- TODO A: a", "  - DONE: a1", "  some note"]
+/// );
+/// # }
+/// ```
+pub fn task_lines(task: &Task, tags: &Tags, style: TaskStyle) -> Vec<String> {
+    let marker = tags.items.first().map(String::as_str).unwrap_or(tag::ITEM);
+    let mut lines = vec![item_line(
+        marker,
+        &task.msg,
+        task.state,
+        task.priority.as_deref(),
+        task.completed,
+        tags,
+        style,
+    )];
+    for st in &task.subtasks {
+        let line = item_line(marker, &st.msg, st.state, None, None, tags, style);
+        lines.push(format!("{}{}", tag::SUB, line));
+    }
+    for n in &task.notes {
+        lines.push(format!("{}{}", tag::SUB, n));
+    }
+    lines
+}
+
+/// Mark the task or subtask with the given span as done on the given date.  
+/// The date takes the place of the priority (```DONE 2019-10-18: x```);
+/// tasks with a priority and checkboxes get a ```done:2019-10-18``` marker instead.
+/// Subtasks have no completion date, so they are only marked as done.
+///
+/// # Example:
+/// ```
+/// # extern crate chrono;
+/// # fn main(){
+/// use chrono::naive::NaiveDate;
+/// use mdlog::parser::MDLogParser;
+/// use mdlog::writer::mark_done;
+///
+/// let log = "## Mon, 14.10.2019\n- TODO: a\n  - [ ] a1\n";
+/// let p = MDLogParser::normalizing();
+/// let task = &p.parse_tasks(log).unwrap()[0];
+/// let date = NaiveDate::from_ymd(2019, 10, 18);
+///
+/// let log = mark_done(log, &task.subtasks[0].span, p.tags(), date).unwrap();
+/// let log = mark_done(&log, &task.span, p.tags(), date).unwrap();
+///
+/// assert_eq!(log, "## Mon, 14.10.2019\n- DONE 2019-10-18: a\n  - [x] a1\n");
+/// assert_eq!(p.parse_tasks(&log).unwrap()[0].completed, Some(date));
+/// # }
+/// ```
+pub fn mark_done(log_data: &str, span: &Span, tags: &Tags, date: NaiveDate) -> io::Result<String> {
+    let not_a_task = || {
+        io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("There is no task at line {}", span.line),
+        )
+    };
+
+    let start = span.byte_range.start;
+    let line = log_data
+        .get(start..)
+        .ok_or_else(not_a_task)?
+        .split('\n')
+        .next()
+        .unwrap_or("")
+        .trim_end_matches('\r');
+    let item = tags.strip_item(line).ok_or_else(not_a_task)?;
+    let task_tag = tags.task_tag(item).ok_or_else(not_a_task)?;
+
+    // a date in place of the priority is replaced
+    let priority = task_tag
+        .priority(item)
+        .filter(|p| NaiveDate::parse_from_str(p, "%Y-%m-%d").is_err());
+    let marker = &line[..line.len() - item.len()];
+    // subtasks are the indented items of a unit
+    let completed = Some(date).filter(|_| span.column == 1);
+    let done = item_line(
+        marker,
+        task_tag.msg(item),
+        TaskState::Done,
+        priority,
+        completed,
+        tags,
+        task_tag.style,
+    );
+
+    let mut out = String::with_capacity(log_data.len() + done.len());
+    out.push_str(&log_data[..start]);
+    out.push_str(&done);
+    out.push_str(&log_data[start + line.len()..]);
+    Ok(out)
+}
+
+pub(crate) fn item_line(
+    marker: &str,
+    msg: &str,
+    state: TaskState,
+    priority: Option<&str>,
+    completed: Option<NaiveDate>,
+    tags: &Tags,
+    style: TaskStyle,
+) -> String {
+    // a completion date already given by a marker is kept as it is
+    let completed = completed
+        .filter(|_| state.is_closed() && marker_date(msg, tag::DONE_MARKER).is_none())
+        .map(|d| d.format("%Y-%m-%d").to_string());
+
+    let checkbox = match (style, priority) {
+        // checkboxes have no place for a priority
+        (TaskStyle::Checkbox, None) => tags.tag_for(state, TaskStyle::Checkbox),
+        _ => None,
+    };
+    if let Some(checkbox) = checkbox {
+        return match completed {
+            Some(d) => format!("{}{} {} {}{}", marker, checkbox, msg, tag::DONE_MARKER, d),
+            None => format!("{}{} {}", marker, checkbox, msg),
+        };
+    }
+
+    let keyword = tags.tag_for(state, TaskStyle::Keyword).unwrap_or(tag::TODO);
+    match (priority, completed) {
+        (Some(p), Some(d)) => format!(
+            "{}{} {}: {} {}{}",
+            marker,
+            keyword,
+            p,
+            msg,
+            tag::DONE_MARKER,
+            d
+        ),
+        (Some(p), None) => format!("{}{} {}: {}", marker, keyword, p, msg),
+        (None, Some(d)) => format!("{}{} {}: {}", marker, keyword, d, msg),
+        (None, None) => format!("{}{}: {}", marker, keyword, msg),
+    }
+}
+
+/// Insert the given lines at the end of the day with the given date.  
+/// If there is no heading for the day yet, it is added in front of the first later day or week,
+/// or at the end of the log. Lines in code blocks are never mistaken for headings.
+///
+/// # Example:
+/// ```
+/// # extern crate chrono;
+/// # fn main(){
+/// use chrono::naive::NaiveDate;
+/// use mdlog::format::LogFormat;
+/// use mdlog::writer::insert_under_day;
+///
+/// let log = "## Mon, 14.10.2019\n- a\n\n## Wed, 16.10.2019\n- c\n";
+/// let format = LogFormat::default();
+///
+/// let log = insert_under_day(log, &format, NaiveDate::from_ymd(2019, 10, 14), &["- b".into()]);
+/// let log = insert_under_day(&log, &format, NaiveDate::from_ymd(2019, 10, 15), &["- x".into()]);
+///
+/// assert_eq!(
+///     log,
+///     "## Mon, 14.10.2019\n- a\n- b\n\n## Tue, 15.10.2019\n- x\n\n## Wed, 16.10.2019\n- c\n"
+/// );
+/// # }
+/// ```
+pub fn insert_under_day(
+    log_data: &str,
+    format: &LogFormat,
+    date: NaiveDate,
+    new_lines: &[String],
+) -> String {
+    let line_end = parser::detect_line_end(log_data);
+    let mut lines: Vec<String> = log_data.lines().map(String::from).collect();
+
+    // the index of the day heading and of the first later heading
+    let mut day = None;
+    let mut later = None;
+    let mut in_code = false;
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim_start().starts_with(tag::CODE_FENCE) {
+            in_code = !in_code;
+        }
+        if in_code {
+            continue;
+        }
+
+        if let Some(heading) = line.strip_prefix(tag::WEEK) {
+            if day.is_some() {
+                later = Some(i);
+                break;
+            }
+            if let Ok((_, start, _)) = format.parse_week_heading(heading) {
+                if start > date {
+                    later = Some(i);
+                    break;
+                }
+            }
+        } else if let Some(heading) = line.strip_prefix(tag::DAY) {
+            if day.is_some() {
+                later = Some(i);
+                break;
+            }
+            match format.parse_day_heading(heading) {
+                Ok(d) if d == date => day = Some(i),
+                Ok(d) if d > date => {
+                    later = Some(i);
+                    break;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let end = later.unwrap_or(lines.len());
+    if day.is_some() {
+        // right after the last non-blank line of the day
+        let pos = (0..end)
+            .rev()
+            .find(|i| !lines[*i].trim().is_empty())
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        lines.splice(pos..pos, new_lines.iter().cloned());
+    } else {
+        let mut block = vec![];
+        if end > 0 && !lines[end - 1].trim().is_empty() {
+            block.push(String::new());
+        }
+        block.push(format!("{}{}", tag::DAY, format.day_heading(date)));
+        block.extend(new_lines.iter().cloned());
+        if end < lines.len() {
+            block.push(String::new());
+        }
+        lines.splice(end..end, block);
+    }
+
+    let mut out = lines.join(line_end);
+    out.push_str(line_end);
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_DATA: &str = "# Week 42, 14.10.2019 - 20.10.2019
+
+## Tue, 15.10.2019
+- a
+```
+## Thu, 17.10.2019
+```
+
+# Week 43, 21.10.2019 - 27.10.2019
+";
+
+    #[test]
+    fn insert_days() {
+        let format = LogFormat::default();
+        let day = |d| NaiveDate::from_ymd(2019, 10, d);
+        let x = ["- x".to_string()];
+
+        let log = insert_under_day(EXAMPLE_DATA, &format, day(17), &x);
+        let log = insert_under_day(&log, &format, day(14), &x);
+        let log = insert_under_day(&log, &format, day(28), &x);
+
+        assert_eq!(
+            log,
+            "# Week 42, 14.10.2019 - 20.10.2019
+
+## Mon, 14.10.2019
+- x
+
+## Tue, 15.10.2019
+- a
+```
+## Thu, 17.10.2019
+```
+
+## Thu, 17.10.2019
+- x
+
+# Week 43, 21.10.2019 - 27.10.2019
+
+## Mon, 28.10.2019
+- x
+"
+        );
+
+        let windows = EXAMPLE_DATA.replace('\n', "\r\n");
+        let log = insert_under_day(&windows, &format, day(15), &x);
+        assert!(log.contains("```\r\n- x\r\n\r\n# Week 43"));
+    }
+}